@@ -408,7 +408,10 @@ impl TicTacToePlugin {
         current_turn: Res<CurrentTurn>,
         players: Query<(&Player, &Symbol)>,
     ) {
-        for FromClient { client_id, event } in pick_events.iter().copied() {
+        for FromClient {
+            client_id, event, ..
+        } in pick_events.iter().copied()
+        {
             // It's good to check the received data, client could be cheating.
             if event.0 > GRID_SIZE * GRID_SIZE {
                 error!("received invalid cell index {:?}", event.0);
@@ -512,6 +515,7 @@ impl TicTacToePlugin {
 }
 
 /// Returns `true` if the local player can select cells.
+#[allow(clippy::type_complexity)]
 fn local_player_turn(
 ) -> impl FnMut(Res<CurrentTurn>, Option<Res<NetcodeClientTransport>>, Query<(&Player, &Symbol)>) -> bool
 {
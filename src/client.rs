@@ -10,6 +10,7 @@ use bevy_renet::{renet::RenetClient, transport::NetcodeClientPlugin, RenetClient
 use bincode::{DefaultOptions, Options};
 
 use crate::replicon_core::{
+    compression::NetworkCompression,
     replication_rules::{Mapper, Replication, ReplicationRules},
     NetworkTick, REPLICATION_CHANNEL_ID,
 };
@@ -54,6 +55,7 @@ impl ClientPlugin {
             world.resource_scope(|world, mut entity_map: Mut<NetworkEntityMap>| {
                 world.resource_scope(|world, replication_rules: Mut<ReplicationRules>| {
                     while let Some(message) = client.receive_message(REPLICATION_CHANNEL_ID) {
+                        let message = world.resource::<NetworkCompression>().decompress(message)?;
                         let end_pos: u64 = message.len().try_into().unwrap();
                         let mut cursor = Cursor::new(message);
 
@@ -237,9 +239,23 @@ pub enum ClientSet {
 pub struct NetworkEntityMap {
     server_to_client: HashMap<Entity, Entity>,
     client_to_server: HashMap<Entity, Entity>,
+
+    /// Whether unmapped entities are local entities of the same world.
+    ///
+    /// If `true`, [`ClientMapper`] maps them to themselves if they exist
+    /// or to [`Entity::PLACEHOLDER`] otherwise instead of spawning new entities.
+    local: bool,
 }
 
 impl NetworkEntityMap {
+    /// Creates an empty map for entities of the same world.
+    pub(crate) fn local() -> Self {
+        Self {
+            local: true,
+            ..Default::default()
+        }
+    }
+
     #[inline]
     pub fn insert(&mut self, server_entity: Entity, client_entity: Entity) {
         self.server_to_client.insert(server_entity, client_entity);
@@ -294,6 +310,7 @@ pub struct ClientMapper<'a> {
     world: &'a mut World,
     server_to_client: &'a mut HashMap<Entity, Entity>,
     client_to_server: &'a mut HashMap<Entity, Entity>,
+    local: bool,
 }
 
 impl<'a> ClientMapper<'a> {
@@ -303,12 +320,21 @@ impl<'a> ClientMapper<'a> {
             world,
             server_to_client: &mut entity_map.server_to_client,
             client_to_server: &mut entity_map.client_to_server,
+            local: entity_map.local,
         }
     }
 }
 
 impl Mapper for ClientMapper<'_> {
     fn map(&mut self, entity: Entity) -> Entity {
+        if self.local {
+            return match self.server_to_client.get(&entity) {
+                Some(&mapped_entity) => mapped_entity,
+                None if self.world.get_entity(entity).is_some() => entity,
+                None => Entity::PLACEHOLDER,
+            };
+        }
+
         *self.server_to_client.entry(entity).or_insert_with(|| {
             let client_entity = self.world.spawn(Replication).id();
             self.client_to_server.insert(client_entity, entity);
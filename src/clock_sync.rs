@@ -0,0 +1,306 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_renet::{
+    renet::{RenetClient, RenetServer, SendType},
+    transport::client_connected,
+};
+use bincode::{DefaultOptions, Options};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::ClientSet,
+    replicon_core::{NetworkChannels, NetworkTick},
+    server::{has_authority, ServerSet},
+};
+
+/// Estimates round-trip time and clock offset between client and server.
+///
+/// Client periodically sends pings over a dedicated unreliable channel and server
+/// immediately answers with its time and current [`NetworkTick`]. Results are available
+/// in the [`ServerTime`] resource.
+pub struct ClockSyncPlugin {
+    ping_interval: Duration,
+}
+
+impl Default for ClockSyncPlugin {
+    fn default() -> Self {
+        Self {
+            ping_interval: Duration::from_millis(500),
+        }
+    }
+}
+
+impl ClockSyncPlugin {
+    /// Creates plugin with the specified interval between pings.
+    pub fn new(ping_interval: Duration) -> Self {
+        Self { ping_interval }
+    }
+}
+
+impl Plugin for ClockSyncPlugin {
+    fn build(&self, app: &mut App) {
+        let mut channels = app.world.resource_mut::<NetworkChannels>();
+        let ping_channel_id = channels.create_client_channel(SendType::Unreliable);
+        let pong_channel_id = channels.create_server_channel(SendType::Unreliable);
+
+        app.init_resource::<ServerTime>()
+            .insert_resource(ClockSyncSettings {
+                ping_interval: self.ping_interval,
+                ping_channel_id,
+                pong_channel_id,
+            })
+            .add_systems(
+                PreUpdate,
+                (
+                    Self::pong_receiving_system
+                        .in_set(ClientSet::Receive)
+                        .run_if(client_connected()),
+                    Self::ping_receiving_system
+                        .in_set(ServerSet::Receive)
+                        .run_if(resource_exists::<RenetServer>()),
+                    Self::local_update_system
+                        .after(ServerSet::Receive)
+                        .run_if(has_authority())
+                        .run_if(resource_exists::<NetworkTick>()),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    Self::ping_sending_system
+                        .in_set(ClientSet::Send)
+                        .run_if(client_connected()),
+                    Self::reset_system.run_if(resource_removed::<RenetClient>()),
+                ),
+            );
+    }
+}
+
+impl ClockSyncPlugin {
+    fn ping_sending_system(
+        mut last_ping: Local<Option<f64>>,
+        mut client: ResMut<RenetClient>,
+        settings: Res<ClockSyncSettings>,
+        time: Res<Time>,
+    ) {
+        let client_time = time.raw_elapsed_seconds_f64();
+        if last_ping
+            .is_some_and(|last_ping| client_time - last_ping < settings.ping_interval.as_secs_f64())
+        {
+            return;
+        }
+
+        let message = DefaultOptions::new()
+            .serialize(&client_time)
+            .expect("ping should be serializable");
+        client.send_message(settings.ping_channel_id, message);
+        *last_ping = Some(client_time);
+    }
+
+    fn ping_receiving_system(
+        mut server: ResMut<RenetServer>,
+        settings: Res<ClockSyncSettings>,
+        network_tick: Res<NetworkTick>,
+        time: Res<Time>,
+    ) {
+        for client_id in server.clients_id() {
+            while let Some(message) = server.receive_message(client_id, settings.ping_channel_id) {
+                match DefaultOptions::new().deserialize::<f64>(&message) {
+                    Ok(client_time) => {
+                        let pong = Pong {
+                            client_time,
+                            server_time: time.raw_elapsed_seconds_f64(),
+                            tick: *network_tick,
+                        };
+                        let message = DefaultOptions::new()
+                            .serialize(&pong)
+                            .expect("pong should be serializable");
+                        server.send_message(client_id, settings.pong_channel_id, message);
+                    }
+                    Err(e) => error!("unable to deserialize ping from client {client_id}: {e}"),
+                }
+            }
+        }
+    }
+
+    fn pong_receiving_system(
+        mut server_time: ResMut<ServerTime>,
+        mut client: ResMut<RenetClient>,
+        settings: Res<ClockSyncSettings>,
+        time: Res<Time>,
+    ) {
+        let client_time = time.raw_elapsed_seconds_f64();
+        while let Some(message) = client.receive_message(settings.pong_channel_id) {
+            let pong: Pong = DefaultOptions::new()
+                .deserialize(&message)
+                .expect("server should send valid pongs");
+            server_time.add_sample(pong, client_time);
+        }
+
+        if server_time.is_synced() {
+            server_time.update(client_time);
+        }
+    }
+
+    /// Keeps [`ServerTime`] in sync with local time when we have authority.
+    fn local_update_system(
+        mut server_time: ResMut<ServerTime>,
+        network_tick: Res<NetworkTick>,
+        time: Res<Time>,
+    ) {
+        *server_time = ServerTime {
+            synced: true,
+            elapsed: time.raw_elapsed_seconds_f64(),
+            tick: *network_tick,
+            ..Default::default()
+        };
+    }
+
+    fn reset_system(mut server_time: ResMut<ServerTime>) {
+        *server_time = Default::default();
+    }
+}
+
+/// Estimated server clock.
+///
+/// Updated every frame on client while connected. Also kept in sync with local time and [`NetworkTick`]
+/// when we have authority, so the same code can be used on server and in single-player.
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub struct ServerTime {
+    synced: bool,
+
+    /// Smoothed round-trip time in seconds.
+    rtt: f64,
+
+    /// Smoothed difference between server and client clocks in seconds.
+    offset: f64,
+
+    /// Server time and tick from the first pong, used to estimate tick rate.
+    first_sample: Option<(f64, NetworkTick)>,
+
+    /// Server time and tick from the last pong.
+    last_sample: Option<(f64, NetworkTick)>,
+
+    /// Estimated number of server ticks per second.
+    tick_rate: Option<f64>,
+
+    /// Estimated server time in seconds for the current frame.
+    elapsed: f64,
+
+    /// Estimated server tick for the current frame.
+    tick: NetworkTick,
+
+    /// Estimated fraction of the current server tick that have passed.
+    overstep: f32,
+}
+
+impl ServerTime {
+    /// Weight of a new sample for smoothing.
+    const SMOOTHING: f64 = 0.1;
+
+    /// Returns `true` if at least one pong was received or we have authority.
+    pub fn is_synced(&self) -> bool {
+        self.synced
+    }
+
+    /// Returns smoothed round-trip time.
+    pub fn rtt(&self) -> Duration {
+        Duration::from_secs_f64(self.rtt)
+    }
+
+    /// Returns smoothed difference between server and client clocks in seconds.
+    ///
+    /// Add it to [`Time::raw_elapsed_seconds_f64`] to get server time.
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// Returns estimated server time in seconds since server startup.
+    pub fn elapsed_seconds(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// Returns estimated current server tick.
+    pub fn tick(&self) -> NetworkTick {
+        self.tick
+    }
+
+    /// Returns estimated fraction of the current server tick that have passed in range `0.0..1.0`.
+    ///
+    /// Useful for interpolation.
+    pub fn overstep(&self) -> f32 {
+        self.overstep
+    }
+
+    /// Returns estimated number of server ticks per second.
+    ///
+    /// Requires at least two pongs with different ticks.
+    pub fn tick_rate(&self) -> Option<f64> {
+        self.tick_rate
+    }
+
+    fn add_sample(&mut self, pong: Pong, client_time: f64) {
+        let rtt = (client_time - pong.client_time).max(0.0);
+        let offset = pong.server_time + rtt / 2.0 - client_time;
+        if self.synced {
+            self.rtt += (rtt - self.rtt) * Self::SMOOTHING;
+            self.offset += (offset - self.offset) * Self::SMOOTHING;
+        } else {
+            self.rtt = rtt;
+            self.offset = offset;
+            self.synced = true;
+        }
+
+        if self
+            .last_sample
+            .is_some_and(|(server_time, _)| server_time > pong.server_time)
+        {
+            // Reordered pong.
+            return;
+        }
+
+        let (first_time, first_tick) = *self
+            .first_sample
+            .get_or_insert((pong.server_time, pong.tick));
+        let ticks = pong.tick.get().wrapping_sub(first_tick.get());
+        let seconds = pong.server_time - first_time;
+        if ticks > 0 && seconds > 0.0 {
+            self.tick_rate = Some(ticks as f64 / seconds);
+        }
+        self.last_sample = Some((pong.server_time, pong.tick));
+    }
+
+    fn update(&mut self, client_time: f64) {
+        self.elapsed = client_time + self.offset;
+
+        let Some((sample_time, sample_tick)) = self.last_sample else {
+            return;
+        };
+        let Some(tick_rate) = self.tick_rate else {
+            self.tick = sample_tick;
+            return;
+        };
+
+        let ticks = ((self.elapsed - sample_time) * tick_rate).max(0.0);
+        self.tick = NetworkTick::new(sample_tick.get().wrapping_add(ticks as u32));
+        self.overstep = ticks.fract() as f32;
+    }
+}
+
+#[derive(Resource)]
+struct ClockSyncSettings {
+    ping_interval: Duration,
+    ping_channel_id: u8,
+    pong_channel_id: u8,
+}
+
+#[derive(Deserialize, Serialize)]
+struct Pong {
+    /// Client time from the ping.
+    client_time: f64,
+    /// Server time when the ping was received.
+    server_time: f64,
+    /// Server tick when the ping was received.
+    tick: NetworkTick,
+}
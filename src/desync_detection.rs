@@ -0,0 +1,476 @@
+use std::{hash::Hasher, mem};
+
+use bevy::{
+    ecs::{archetype::ArchetypeId, component::ComponentId, world::EntityRef},
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_renet::{
+    renet::{RenetClient, RenetServer, SendType},
+    transport::client_connected,
+};
+use bincode::{DefaultOptions, Options};
+use serde::Serialize;
+
+use crate::{
+    client::{ClientSet, LastTick, NetworkEntityMap},
+    replicon_core::{
+        replication_rules::{Ignored, MapNetworkEntities, Mapper, Replication},
+        NetworkChannels, NetworkTick,
+    },
+    server::{rooms::RoomId, rooms::Rooms, ReplicatedClients, ServerSet},
+};
+
+/// Maximum number of received checksums that wait for the corresponding update on client.
+///
+/// Checksums are sent only along with updates, so this limit is reached only if updates are lost.
+const MAX_RECEIVED_CHECKSUMS: usize = 32;
+
+/// Detects divergence of replicated state on clients.
+///
+/// For components registered with [`ChecksumAppExt`], server computes a checksum of their values
+/// on each tick and sends it to clients that received an update on this tick. After applying the update,
+/// client computes the same checksum and emits [`DesyncDetected`] on mismatch.
+/// Checksums of entities without changed components are reused from the previous tick.
+///
+/// Catches client code that mutates replicated state and non-deterministic simulation.
+/// Checksums are verified only for ticks on which the client received an update.
+/// Should be added on both server and client with the same settings.
+#[derive(Default)]
+pub struct DesyncDetectionPlugin {
+    debug: bool,
+}
+
+impl DesyncDetectionPlugin {
+    /// Creates plugin with the specified debug mode.
+    ///
+    /// In debug mode server sends a checksum for each entity, so clients can report
+    /// entities that differ in [`DesyncDetected::entities`]. Increases traffic significantly.
+    pub fn new(debug: bool) -> Self {
+        Self { debug }
+    }
+}
+
+impl Plugin for DesyncDetectionPlugin {
+    fn build(&self, app: &mut App) {
+        let channel_id = app
+            .world
+            .resource_mut::<NetworkChannels>()
+            .create_server_channel(SendType::Unreliable);
+
+        app.add_event::<DesyncDetected>()
+            .init_resource::<ChecksumRules>()
+            .init_resource::<EntityChecksums>()
+            .init_resource::<ReceivedChecksums>()
+            .insert_resource(ChecksumSettings {
+                debug: self.debug,
+                channel_id,
+            })
+            .add_systems(
+                PreUpdate,
+                Self::verification_system
+                    .after(ClientSet::Receive)
+                    .run_if(client_connected()),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    Self::sending_system
+                        .in_set(ServerSet::SendEvents)
+                        .run_if(resource_changed::<NetworkTick>())
+                        .run_if(resource_exists::<RenetServer>()),
+                    Self::reset_system.run_if(resource_removed::<RenetClient>()),
+                ),
+            );
+    }
+}
+
+impl DesyncDetectionPlugin {
+    fn sending_system(world: &mut World) {
+        world.resource_scope(|world, mut entity_checksums: Mut<EntityChecksums>| {
+            entity_checksums.update(world);
+        });
+
+        let entity_checksums = world.resource::<EntityChecksums>();
+        let settings = world.resource::<ChecksumSettings>();
+        let rooms = world.resource::<Rooms>();
+        let network_tick = *world.resource::<NetworkTick>();
+
+        let mut messages = Vec::new();
+        for &client_id in &world.resource::<ReplicatedClients>().0 {
+            let visible_entities =
+                entity_checksums
+                    .entities
+                    .iter()
+                    .filter_map(|(&entity, entity_checksum)| {
+                        let checksum = entity_checksum.checksum?;
+                        rooms
+                            .is_visible(entity_checksum.room_id, client_id)
+                            .then_some((entity, checksum))
+                    });
+            let checksum = visible_entities
+                .clone()
+                .fold(0u64, |sum, (_, checksum)| sum.wrapping_add(checksum));
+            let entity_checksums: Option<Vec<_>> =
+                settings.debug.then(|| visible_entities.collect());
+
+            let message = DefaultOptions::new()
+                .serialize(&(network_tick, checksum, entity_checksums))
+                .expect("checksum should be serializable");
+            messages.push((client_id, message));
+        }
+
+        let channel_id = settings.channel_id;
+        let mut server = world.resource_mut::<RenetServer>();
+        for (client_id, message) in messages {
+            server.send_message(client_id, channel_id, message);
+        }
+    }
+
+    fn verification_system(world: &mut World) {
+        let channel_id = world.resource::<ChecksumSettings>().channel_id;
+        world.resource_scope(|world, mut client: Mut<RenetClient>| {
+            let mut received_checksums = world.resource_mut::<ReceivedChecksums>();
+            while let Some(message) = client.receive_message(channel_id) {
+                let checksum: Checksum = DefaultOptions::new()
+                    .deserialize(&message)
+                    .expect("server should send valid checksums");
+                received_checksums.checksums.push(checksum);
+            }
+        });
+
+        let last_tick = **world.resource::<LastTick>();
+        let mut received_checksums = world.resource_mut::<ReceivedChecksums>();
+        let index = received_checksums
+            .checksums
+            .iter()
+            .position(|(tick, ..)| *tick == last_tick);
+        let current = index.map(|index| received_checksums.checksums.swap_remove(index));
+        received_checksums.prune(last_tick);
+        let Some((tick, server_checksum, server_entities)) = current else {
+            return;
+        };
+        received_checksums.last_verified = Some(tick);
+
+        let checksum_rules = world.resource::<ChecksumRules>();
+        let entity_map = world.resource::<NetworkEntityMap>();
+        let client_entities: HashMap<_, _> = entity_map
+            .to_server()
+            .iter()
+            .filter_map(|(&client_entity, &server_entity)| {
+                let entity = world.get_entity(client_entity)?;
+                let checksum = checksum_rules.entity_checksum(
+                    &entity,
+                    server_entity,
+                    entity_map.to_server(),
+                )?;
+                Some((server_entity, (client_entity, checksum)))
+            })
+            .collect();
+
+        let checksum = client_entities
+            .values()
+            .fold(0u64, |sum, &(_, checksum)| sum.wrapping_add(checksum));
+        if checksum == server_checksum {
+            return;
+        }
+
+        let mut entities = Vec::new();
+        if let Some(server_entities) = server_entities {
+            let server_entities: HashMap<_, _> = server_entities.into_iter().collect();
+            for (server_entity, &(client_entity, checksum)) in &client_entities {
+                if server_entities.get(server_entity) != Some(&checksum) {
+                    entities.push(client_entity);
+                }
+            }
+            for server_entity in server_entities.keys() {
+                if !client_entities.contains_key(server_entity) {
+                    if let Some(&client_entity) = entity_map.to_client().get(server_entity) {
+                        entities.push(client_entity);
+                    }
+                }
+            }
+        }
+
+        warn!("detected desync on {tick:?} for entities {entities:?}");
+        world.send_event(DesyncDetected { tick, entities });
+    }
+
+    fn reset_system(mut received_checksums: ResMut<ReceivedChecksums>) {
+        received_checksums.checksums.clear();
+        received_checksums.last_verified = None;
+    }
+}
+
+/// An extension trait for [`App`] for registering components for desync detection.
+pub trait ChecksumAppExt {
+    /// Includes values of component `C` into checksums of [`DesyncDetectionPlugin`].
+    ///
+    /// Values are hashed in their serialized form.
+    /// Components with [`Ignored<C>`] are skipped on server since clients don't receive them.
+    fn checksum_component<C: Component + Serialize>(&mut self) -> &mut Self;
+
+    /// Same as [`Self::checksum_component`], but maps client entities inside the component
+    /// to server entities before hashing.
+    fn checksum_mapped_component<C: Component + Serialize + MapNetworkEntities + Clone>(
+        &mut self,
+    ) -> &mut Self;
+}
+
+impl ChecksumAppExt for App {
+    fn checksum_component<C: Component + Serialize>(&mut self) -> &mut Self {
+        let component_id = self.world.init_component::<C>();
+        self.world
+            .resource_mut::<ChecksumRules>()
+            .0
+            .push((component_id, serialize_component::<C>));
+        self
+    }
+
+    fn checksum_mapped_component<C: Component + Serialize + MapNetworkEntities + Clone>(
+        &mut self,
+    ) -> &mut Self {
+        let component_id = self.world.init_component::<C>();
+        self.world
+            .resource_mut::<ChecksumRules>()
+            .0
+            .push((component_id, serialize_mapped_component::<C>));
+        self
+    }
+}
+
+/// Serializes component `C` of the entity if it's present and not ignored.
+fn serialize_component<C: Component + Serialize>(
+    entity: &EntityRef,
+    _to_server: &HashMap<Entity, Entity>,
+) -> Option<Vec<u8>> {
+    if entity.contains::<Ignored<C>>() {
+        return None;
+    }
+    let component = entity.get::<C>()?;
+    let bytes = DefaultOptions::new()
+        .serialize(component)
+        .expect("checksummed component should be serializable");
+    Some(bytes)
+}
+
+/// Like [`serialize_component`], but maps entities inside the component using `to_server` map.
+fn serialize_mapped_component<C: Component + Serialize + MapNetworkEntities + Clone>(
+    entity: &EntityRef,
+    to_server: &HashMap<Entity, Entity>,
+) -> Option<Vec<u8>> {
+    if entity.contains::<Ignored<C>>() {
+        return None;
+    }
+    let mut component = entity.get::<C>()?.clone();
+    component.map_entities(&mut ServerMapper(to_server));
+    let bytes = DefaultOptions::new()
+        .serialize(&component)
+        .expect("checksummed component should be serializable");
+    Some(bytes)
+}
+
+/// An event indicating that replicated state on client differs from server.
+///
+/// Emitted only on client.
+#[derive(Clone, Debug, Event)]
+pub struct DesyncDetected {
+    /// Server tick for which checksums didn't match.
+    pub tick: NetworkTick,
+
+    /// Client entities that differ from server.
+    ///
+    /// Available only in debug mode, see [`DesyncDetectionPlugin::new`].
+    pub entities: Vec<Entity>,
+}
+
+/// Maps client entities to server entities.
+///
+/// Entities without mapping are left as is, so on server it maps entities to themselves.
+struct ServerMapper<'a>(&'a HashMap<Entity, Entity>);
+
+impl Mapper for ServerMapper<'_> {
+    fn map(&mut self, entity: Entity) -> Entity {
+        self.0.get(&entity).copied().unwrap_or(entity)
+    }
+}
+
+type SerializeFn = fn(&EntityRef, &HashMap<Entity, Entity>) -> Option<Vec<u8>>;
+
+/// Checksummed components with their serialization functions.
+#[derive(Default, Resource)]
+struct ChecksumRules(Vec<(ComponentId, SerializeFn)>);
+
+impl ChecksumRules {
+    /// Computes checksum of all checksummed components of the entity.
+    ///
+    /// Returns [`None`] if the entity doesn't have any of them.
+    fn entity_checksum(
+        &self,
+        entity: &EntityRef,
+        server_entity: Entity,
+        to_server: &HashMap<Entity, Entity>,
+    ) -> Option<u64> {
+        // Write values as little-endian bytes explicitly,
+        // because `Hash` implementations depend on the platform.
+        let mut hasher = ChecksumHasher::default();
+        hasher.write(&server_entity.to_bits().to_le_bytes());
+        let mut found = false;
+        for (index, (_, serialize)) in self.0.iter().enumerate() {
+            if let Some(bytes) = (serialize)(entity, to_server) {
+                hasher.write(&(index as u32).to_le_bytes());
+                hasher.write(&(bytes.len() as u64).to_le_bytes());
+                hasher.write(&bytes);
+                found = true;
+            }
+        }
+
+        found.then(|| hasher.finish())
+    }
+
+    /// Returns `true` if any checksummed component of the entity changed since the last run of the system.
+    ///
+    /// Should be called inside an exclusive system.
+    fn is_changed(&self, world: &World, entity: &EntityRef) -> bool {
+        let last_run = world.last_change_tick();
+        let this_run = world.read_change_tick();
+        self.0.iter().any(|&(component_id, _)| {
+            entity
+                .get_change_ticks_by_id(component_id)
+                .is_some_and(|ticks| ticks.is_changed(last_run, this_run))
+        })
+    }
+}
+
+/// Checksums of replicated entities computed on server.
+///
+/// Used only on server.
+#[derive(Default, Resource)]
+struct EntityChecksums {
+    entities: HashMap<Entity, EntityChecksum>,
+}
+
+impl EntityChecksums {
+    /// Updates checksums of all replicated entities.
+    ///
+    /// Checksums are recomputed only for entities that changed their archetype
+    /// or any of checksummed components since the last run of the system.
+    /// Should be called inside an exclusive system.
+    fn update(&mut self, world: &World) {
+        let checksum_rules = world.resource::<ChecksumRules>();
+        let marker_id = world
+            .component_id::<Replication>()
+            .expect("replication marker should be registered");
+        let server_map = HashMap::default();
+        let mut previous = mem::take(&mut self.entities);
+        for archetype in world
+            .archetypes()
+            .iter()
+            .filter(|archetype| archetype.contains(marker_id))
+        {
+            for archetype_entity in archetype.entities() {
+                let entity = world.entity(archetype_entity.entity());
+                let cached = previous.remove(&entity.id()).filter(|cached| {
+                    cached.archetype_id == archetype.id()
+                        && !checksum_rules.is_changed(world, &entity)
+                });
+                let checksum = match cached {
+                    Some(cached) => cached.checksum,
+                    None => checksum_rules.entity_checksum(&entity, entity.id(), &server_map),
+                };
+                self.entities.insert(
+                    entity.id(),
+                    EntityChecksum {
+                        archetype_id: archetype.id(),
+                        room_id: entity.get::<RoomId>().copied(),
+                        checksum,
+                    },
+                );
+            }
+        }
+    }
+}
+
+struct EntityChecksum {
+    /// Archetype of the entity when the checksum was computed.
+    ///
+    /// Changes on insertion or removal of any component.
+    archetype_id: ArchetypeId,
+    room_id: Option<RoomId>,
+    /// [`None`] if the entity doesn't have any checksummed components.
+    checksum: Option<u64>,
+}
+
+/// 64-bit FNV-1a hasher.
+///
+/// Unlike [`DefaultHasher`](std::collections::hash_map::DefaultHasher), the algorithm and the seed
+/// are fixed, so server and clients produce the same checksums even if built with different Rust versions.
+struct ChecksumHasher(u64);
+
+impl Default for ChecksumHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for ChecksumHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[derive(Resource)]
+struct ChecksumSettings {
+    debug: bool,
+    channel_id: u8,
+}
+
+/// Server tick, total checksum and optional per-entity checksums.
+type Checksum = (NetworkTick, u64, Option<Vec<(Entity, u64)>>);
+
+/// Checksums received from server that wait for the corresponding update.
+///
+/// Used only on client.
+#[derive(Default, Resource)]
+pub struct ReceivedChecksums {
+    checksums: Vec<Checksum>,
+
+    /// Tick of the last checksum compared with the client state.
+    last_verified: Option<NetworkTick>,
+}
+
+impl ReceivedChecksums {
+    /// Removes checksums for already applied ticks and the oldest checksums above [`MAX_RECEIVED_CHECKSUMS`].
+    fn prune(&mut self, last_tick: NetworkTick) {
+        self.checksums.retain(|(tick, ..)| *tick > last_tick);
+        if self.checksums.len() > MAX_RECEIVED_CHECKSUMS {
+            self.checksums.sort_unstable_by(|(a, ..), (b, ..)| {
+                a.partial_cmp(b).expect("ticks should be comparable")
+            });
+            let excess = self.checksums.len() - MAX_RECEIVED_CHECKSUMS;
+            self.checksums.drain(..excess);
+        }
+    }
+
+    /// Returns the number of checksums that wait for the corresponding update.
+    pub fn len(&self) -> usize {
+        self.checksums.len()
+    }
+
+    /// Returns `true` if there are no checksums that wait for the corresponding update.
+    pub fn is_empty(&self) -> bool {
+        self.checksums.is_empty()
+    }
+
+    /// Returns tick of the last checksum that was compared with the client state.
+    pub fn last_verified(&self) -> Option<NetworkTick> {
+        self.last_verified
+    }
+}
@@ -54,8 +54,8 @@ By default, no components are replicated. A component will be replicated if it h
 In other words you need two things to start replication :
 
 1. Register component type for replication. Component should implement
-[`serde::Serialize`] and [`serde::Deserialize`].
-You can use [`AppReplicationExt::replicate()`] to register the component for replication:
+   [`serde::Serialize`] and [`serde::Deserialize`].
+   You can use [`AppReplicationExt::replicate()`] to register the component for replication:
 
 ```rust
 # use bevy::prelude::*;
@@ -126,25 +126,120 @@ fn deserialize_transform(
 ```
 
 2. You need to choose entities you want to replicate using [`Replication`]
-component. Just insert it to the entity you want to replicate. Only components
-marked for replication through [`AppReplicationExt::replicate()`]
-will be replicated.
+   component. Just insert it to the entity you want to replicate. Only components
+   marked for replication through [`AppReplicationExt::replicate()`]
+   will be replicated.
 
 If you need to disable replication for specific component for specific entity,
 you can insert [`Ignored<T>`] component and replication will be skipped for `T`.
 
+### Rooms
+
+To replicate entities only to a group of clients (for example, to run several
+matches on one server), insert [`RoomId`] component to them and add the clients
+to the room using [`Rooms`] resource on server. Entities without [`RoomId`] are
+replicated to everyone. When a client joins the room, it receives all room entities
+as fresh spawns, and when it leaves, the entities will be despawned on it.
+
+```rust
+# use bevy::prelude::*;
+# use bevy_replicon::prelude::*;
+# let mut app = App::new();
+# app.add_plugins(ReplicationPlugins);
+const MATCH_ROOM: RoomId = RoomId(1);
+# let client_id = 1;
+app.world.spawn((Replication, MATCH_ROOM));
+app.world.resource_mut::<Rooms>().add_client(MATCH_ROOM, client_id);
+```
+
 ### NetworkTick, and fixed timestep games.
 
 The [`ServerPlugin`] sends replication data in `PostUpdate` any time the [`NetworkTick`] resource
 changes. By default, its incremented in `PostUpdate` per the [`TickPolicy`].
 
-If you set [`TickPolicy::Manual`], you can increment [`NetworkTick`] at the start of your
-`FixedTimestep` game loop. This value can represent your simulation step, and is made available
+If your simulation runs in [`FixedUpdate`], set [`TickPolicy::FixedUpdate`] to increment [`NetworkTick`]
+on each fixed step. For custom loops set [`TickPolicy::Manual`] and increment [`NetworkTick`]
+at the start of your game loop. This value can represent your simulation step, and is made available
 to the client in the custom deserialization, despawn and component removal functions.
 
 One use for this is rollback networking: you may want to rollback time and apply the update
 for the tick frame, which is in the past, then resimulate.
 
+To capture the state for rollback, use [`ReplicationSnapshot::capture()`] and restore it later with
+[`ReplicationSnapshot::restore()`]. It uses serialization functions of replicated components,
+so [`Reflect`] is not required:
+
+```rust
+# use bevy::prelude::*;
+# use bevy_replicon::prelude::*;
+# let mut world = World::new();
+# world.init_resource::<ReplicationRules>();
+let snapshot = ReplicationSnapshot::capture(&world)?;
+// Predict or simulate something...
+snapshot.restore(&mut world)?;
+# Ok::<(), bincode::Error>(())
+```
+
+By default every client receives an update on each tick. To send updates less often to clients
+with high RTT, packet loss or bandwidth usage, insert the [`AdaptiveSendRate`] resource on server.
+Skipped changes are not lost and will be included in the next update for the client.
+Current per-client intervals are available in [`ClientSendRates`].
+
+### Server time
+
+[`ClockSyncPlugin`] periodically exchanges pings between client and server to estimate
+round-trip time and clock offset. The results are available in the [`ServerTime`] resource
+with the estimated current server time and [`NetworkTick`], which is useful for interpolation,
+countdown timers and lag compensation:
+
+```rust
+# use bevy::prelude::*;
+# use bevy_replicon::prelude::*;
+# let mut app = App::new();
+# app.add_plugins(ReplicationPlugins);
+app.add_systems(Update, countdown_system);
+
+fn countdown_system(server_time: Res<ServerTime>) {
+    const MATCH_START: f64 = 60.0;
+    if server_time.is_synced() {
+        let remaining = MATCH_START - server_time.elapsed_seconds();
+        info!("match starts in {remaining:.0} seconds, ping: {:?}", server_time.rtt());
+    }
+}
+```
+
+The ping interval can be configured with [`ClockSyncPlugin::new()`].
+
+### Desync detection
+
+To catch client code that accidentally mutates replicated state or non-deterministic simulation,
+add [`DesyncDetectionPlugin`] and register components with [`ChecksumAppExt::checksum_component()`]
+(or [`ChecksumAppExt::checksum_mapped_component()`] for components with entities).
+Server will send checksums of their values along with replication updates and client will emit [`DesyncDetected`] on mismatch:
+
+```rust
+# use bevy::prelude::*;
+# use bevy_replicon::prelude::*;
+# use serde::{Deserialize, Serialize};
+# let mut app = App::new();
+# app.add_plugins(ReplicationPlugins);
+app.add_plugins(DesyncDetectionPlugin::default())
+    .replicate::<Health>()
+    .checksum_component::<Health>()
+    .add_systems(Update, desync_system);
+
+fn desync_system(mut desync_events: EventReader<DesyncDetected>) {
+    for event in &mut desync_events {
+        error!("state diverged from server on {:?}", event.tick);
+    }
+}
+
+#[derive(Component, Deserialize, Serialize)]
+struct Health(u32);
+```
+
+Use [`DesyncDetectionPlugin::new()`] to enable debug mode that reports which entities differ.
+
 ### "Blueprints" pattern
 
 The idea was borrowed from [iyes_scene_tools](https://github.com/IyesGames/iyes_scene_tools#blueprints-pattern).
@@ -195,6 +290,77 @@ struct Player;
 This pairs nicely with server state serialization and keeps saves clean.
 You can use [`replicate_into_scene`](server::replicate_into_scene) to
 fill `DynamicScene` with replicated entities and their components.
+To load it back on a running server, use [`load_replicated_scene`](server::load_replicated_scene).
+It spawns scene entities for replication and remaps entity references between them,
+so already connected clients will receive them as new entities.
+
+If your components don't implement [`Reflect`], use [`WorldSave`] instead. It writes replicated
+entities in a compact binary format using serialization functions of replicated components
+with a header of component type names:
+
+```rust
+# use std::fs::File;
+# use bevy::prelude::*;
+# use bevy_replicon::prelude::*;
+# fn save(world: &World) -> Result<(), Box<dyn std::error::Error>> {
+WorldSave::capture(world)?.write(File::create("world.sav")?)?;
+# Ok(())
+# }
+# fn load(world: &mut World) -> Result<(), Box<dyn std::error::Error>> {
+WorldSave::read(File::open("world.sav")?)?.load(world)?;
+# Ok(())
+# }
+```
+
+Saves record the version set with [`SaveMigrationAppExt::set_save_version()`]. To keep old saves
+loading after renaming or restructuring a component, register migrations that will be applied
+to saves with older versions:
+
+```rust
+# use bevy::prelude::*;
+# use bevy_replicon::prelude::*;
+# use bincode::{DefaultOptions, Options};
+# use serde::{Deserialize, Serialize};
+# let mut app = App::new();
+# app.add_plugins(ReplicationPlugins);
+app.replicate::<Health>()
+    .set_save_version(1)
+    .rename_saved_component::<Health>("my_game::Hp")
+    .migrate_saved_component::<Health>(1, |bytes| {
+        // Health was stored as `u8` before version 1.
+        let health: u8 = DefaultOptions::new().deserialize(bytes)?;
+        DefaultOptions::new().serialize(&Health(health.into()))
+    });
+
+#[derive(Component, Deserialize, Serialize)]
+struct Health(u32);
+```
+
+For crash recovery on dedicated servers, add [`AutosavePlugin`]. It periodically writes
+[`WorldSave`] on a background task, alternating between two files to never corrupt the
+previous save. Use [`Autosave::latest()`] to restore it on startup:
+
+```rust
+# use std::time::Duration;
+# use bevy::prelude::*;
+# use bevy_replicon::prelude::*;
+# let mut app = App::new();
+# app.add_plugins(ReplicationPlugins);
+app.add_plugins(AutosavePlugin::new("world.sav", Duration::from_secs(60)))
+    .add_systems(Startup, restore_system);
+
+fn restore_system(world: &mut World) {
+    match world.resource::<Autosave>().latest() {
+        Ok(Some(world_save)) => {
+            if let Err(e) = world_save.load(world) {
+                error!("unable to restore autosave: {e}");
+            }
+        }
+        Ok(None) => (),
+        Err(e) => error!("unable to read autosave: {e}"),
+    }
+}
+```
 
 ### Component relations
 
@@ -242,7 +408,7 @@ fn event_sending_system(mut dummy_events: EventWriter<DummyEvent>) {
 }
 
 fn event_receiving_system(mut dummy_events: EventReader<FromClient<DummyEvent>>) {
-    for FromClient { client_id, event } in &mut dummy_events {
+    for FromClient { client_id, event, .. } in &mut dummy_events {
         info!("received event {event:?} from client {client_id}");
     }
 }
@@ -273,13 +439,84 @@ impl MapNetworkEntities for MappedEvent {
 }
 ```
 
+The server can't trust entities received from clients. To reject events with entities
+that are not replicated, not visible to the sender or not owned by it (see [`EntityOwner`]),
+enable validation with [`ClientEventValidationAppExt::validate_client_event()`].
+Rejected events are reported as [`ClientViolation`]:
+
+```rust
+# use bevy::prelude::*;
+# use bevy_replicon::prelude::*;
+# use serde::{Deserialize, Serialize};
+# let mut app = App::new();
+# app.add_plugins(ReplicationPlugins);
+app.add_mapped_client_event::<MappedEvent>(SendPolicy::Ordered)
+    .validate_client_event::<MappedEvent>(EntityValidation::Owned)
+    .add_systems(Update, violation_system);
+
+fn violation_system(mut violations: EventReader<ClientViolation>) {
+    for violation in &mut violations {
+        warn!("client {} sent invalid {}: {:?}", violation.client_id, violation.event_name, violation.kind);
+    }
+}
+# #[derive(Debug, Deserialize, Event, Serialize)]
+# struct MappedEvent(Entity);
+# impl MapNetworkEntities for MappedEvent {
+#     fn map_entities<T: Mapper>(&mut self, mapper: &mut T) {
+#         self.0 = mapper.map(self.0);
+#     }
+# }
+```
+
+To protect the server from flooding, messages of a client event can be limited per client
+with [`ClientEventLimitsAppExt::limit_client_event()`]. See [`EventLimits`] and [`LimitPolicy`]
+for details. Clients that exceed the limits are also reported as [`ClientViolation`].
+
+For lag compensation the server needs to know which tick the client was looking at when
+it sent an event. Call [`ClientEventStampAppExt::stamp_client_event()`] to include [`ClientTick`]
+into [`FromClient::tick`]. If the client interpolates replicated state, it should update
+[`InterpolationOffset`]. To rewind the world on server, record [`ComponentHistory`] of the
+required components with [`ComponentHistoryAppExt::record_history()`]:
+
+```rust
+# use bevy::prelude::*;
+# use bevy_replicon::prelude::*;
+# use serde::{Deserialize, Serialize};
+# let mut app = App::new();
+# app.add_plugins(ReplicationPlugins);
+app.add_client_event::<Shot>(SendPolicy::Ordered)
+    .stamp_client_event::<Shot>()
+    .record_history::<Transform>(30)
+    .add_systems(Update, hit_system.run_if(has_authority()));
+
+fn hit_system(mut shots: EventReader<FromClient<Shot>>, hitboxes: Query<&ComponentHistory<Transform>>) {
+    for FromClient { tick, .. } in &mut shots {
+        let tick = tick.expect("shots should be stamped").tick;
+        for history in &hitboxes {
+            if let Some(transform) = history.get(tick) {
+                // Check the hit against the transform at the moment of the shot.
+            }
+        }
+    }
+}
+# #[derive(Debug, Deserialize, Event, Serialize)]
+# struct Shot;
+```
+
 There is also [`ClientEventAppExt::add_client_reflect_event()`] and [`ClientEventAppExt::add_mapped_client_reflect_event()`]
 for events that require reflection for serialization and deserialization (for example, events that contain `Box<dyn Reflect>`).
-To serialize such event you need to write serializer and deserializer manually because for such types you need access to `AppTypeRegistry`.
-It's pretty straigtforward but requires some boilerplate. See [`BuildEventSerializer`], [`BuildEventDeserializer`] and module
+Such events need access to `AppTypeRegistry` for serialization. For events that derive [`Reflect`]
+you can use [`ClientEventAppExt::add_client_event_reflect()`] that serializes them using [`ReflectEventSerde`].
+It also can be used for events that wrap `Box<dyn Reflect>` if they implement [`ReflectedEvent`].
+For other events you need to write serializer and deserializer manually. It's pretty straigtforward
+but requires some boilerplate. See [`BuildEventSerializer`], [`BuildEventDeserializer`] and module
 `common` module in integration tests as example.
 Don't forget to check what inside every `Box<dyn Reflect>` from a client, it could be anything!
 
+For player input use [`ClientInputAppExt::add_client_input()`] instead. Inputs are sent
+unreliably with redundancy and server releases exactly one [`ClientInput`] per tick for each client
+from a jitter buffer. See [`InputSettings`] and [`MissingInput`] for configuration.
+
 ### From server to client
 
 A similar technique is used to send events from server to clients. To do this,
@@ -318,7 +555,145 @@ struct DummyEvent;
 Just like with client events, if the event contains an entity, then
 [`ServerEventAppExt::add_mapped_server_event()`] should be used instead.
 
-And for events with `Box<dyn Reflect>` you can use [`ServerEventAppExt::add_server_reflect_event()`] and [`ServerEventAppExt::add_mapped_server_reflect_event()`].
+If an entity inside a mapped event can't be mapped (for example, the entity was
+despawned or not replicated yet), the event is dropped by default. This can be
+changed per event with [`EventMappingAppExt::set_mapping_policy()`], see [`MappingPolicy`]
+for available options. Number of affected events can be read from [`EventMappingDiagnostics`].
+
+Events that describe lasting facts (like the current map) can be registered with
+[`StickyEventAppExt::add_sticky_server_event()`]. Broadcasted events will be retained
+according to [`Retention`] and sent to clients that connect later.
+
+For state-like events (like the current objective) use
+[`LatestEventAppExt::add_latest_server_event()`]. Only the latest event (per key, see [`Superseding`])
+will be resent until acknowledged by the client, older events are dropped instead of being queued.
+
+Server events can also be sent to all clients in a room with [`SendMode::Room`]
+or to a list of clients with [`SendMode::Clients`].
+
+By default server events are queued and sent with the next replication message,
+so mapped events won't arrive before entities that were spawned earlier. Use
+[`EventTimingAppExt::set_send_timing()`] with [`SendTiming::Immediate`] to send them
+at the end of the current frame instead.
+
+If you send many small events of the same type per tick, you can call
+[`EventBatchingAppExt::batch_server_event()`] on both server and client to pack
+all events for the same client into a single message per tick.
+
+And for events with `Box<dyn Reflect>` you can use [`ServerEventAppExt::add_server_reflect_event()`] and [`ServerEventAppExt::add_mapped_server_reflect_event()`],
+or [`ServerEventAppExt::add_server_event_reflect()`] for events that derive [`Reflect`].
+
+### Relayed events
+
+For events that clients send to each other (like chat messages or emotes) register
+an event with [`RelayedEventAppExt::add_relayed_event()`]. Clients send [`ToPeers<T>`] with
+a [`SendMode`], the server forwards it and recipients receive [`FromPeer<T>`] with the sender ID.
+Forwarded events can be filtered on server with [`RelayedEventAppExt::set_relay_filter()`].
+If the event contains an entity, use [`RelayedEventAppExt::add_mapped_relayed_event()`].
+
+### Lockstep
+
+Instead of replicating state, you can run a deterministic simulation on every peer.
+Register commands with [`LockstepAppExt::add_lockstep_commands()`] and send them as regular events.
+Server gathers commands from all clients for each step and broadcasts the combined set.
+All peers, including server, receive the same [`LockstepStep`] events and should advance
+the simulation only on them:
+
+```rust
+# use bevy::prelude::*;
+# use bevy_replicon::prelude::*;
+# use serde::{Deserialize, Serialize};
+# let mut app = App::new();
+# app.add_plugins(ReplicationPlugins);
+app.add_lockstep_commands::<MoveUnit>(2)
+    .add_systems(Update, simulation_system);
+
+fn simulation_system(mut steps: EventReader<LockstepStep<MoveUnit>>) {
+    for step in &mut steps {
+        for FromClient { client_id, event, .. } in &step.commands {
+            info!("{client_id} moved {:?} on {:?}", event.unit, step.tick);
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Event, Serialize)]
+struct MoveUnit {
+    unit: Entity,
+    target: Vec2,
+}
+```
+
+### Requests
+
+If the client expects an answer from the server, register a request with
+[`ClientRequestAppExt::add_client_request()`]. The client sends it using
+[`RequestSender`] and the server answers with [`FromClient::respond()`].
+The response is routed back only to the client that made the request and
+emitted as [`RequestResult`] with the same [`RequestId`]:
+
+```rust
+# use std::time::Duration;
+# use bevy::prelude::*;
+# use bevy_replicon::prelude::*;
+# use serde::{Deserialize, Serialize};
+# let mut app = App::new();
+# app.add_plugins(ReplicationPlugins);
+app.add_client_request::<BuyItem, PurchaseResult>(SendPolicy::Ordered, Duration::from_secs(5))
+    .add_systems(Update, (purchase_system, result_system));
+
+fn purchase_system(
+    mut requests: EventReader<FromClient<Request<BuyItem>>>,
+    mut responses: EventWriter<ToClients<Response<PurchaseResult>>>,
+) {
+    for request in &mut requests {
+        responses.send(request.respond(PurchaseResult { success: true }));
+    }
+}
+
+fn result_system(mut results: EventReader<RequestResult<PurchaseResult>>) {
+    for RequestResult { id, result } in &mut results {
+        info!("request {id:?} finished with {result:?}");
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct BuyItem;
+
+#[derive(Debug, Deserialize, Serialize)]
+struct PurchaseResult {
+    success: bool,
+}
+```
+
+### Entity events
+
+Events about a specific replicated entity can be registered with
+[`EntityEventAppExt::add_server_entity_event()`]. Send [`ToEntity`] on server
+and the event will be delivered only to clients that replicate the entity.
+On client it will be emitted as [`EntityEvent`] with already mapped entity.
+If the event arrives before the entity spawn, it will be buffered until the
+entity is replicated:
+
+```rust
+# use bevy::prelude::*;
+# use bevy_replicon::prelude::*;
+# use serde::{Deserialize, Serialize};
+# let mut app = App::new();
+# app.add_plugins(ReplicationPlugins);
+app.add_server_entity_event::<Damaged>(SendPolicy::Unordered)
+    .add_systems(Update, damage_system);
+
+fn damage_system(mut damage_events: EventReader<EntityEvent<Damaged>>) {
+    for EntityEvent { entity, event } in &mut damage_events {
+        info!("{entity:?} received {} damage", event.amount);
+    }
+}
+
+#[derive(Debug, Deserialize, Event, Serialize)]
+struct Damaged {
+    amount: u32,
+}
+```
 
 ## Server and client creation
 
@@ -376,29 +751,63 @@ To reduce packet size there are the following limits per replication update:
 */
 
 pub mod client;
+pub mod clock_sync;
+pub mod desync_detection;
 pub mod network_event;
 pub mod parent_sync;
 pub mod replicon_core;
+pub mod save;
 pub mod server;
 
 pub mod prelude {
     pub use super::{
         client::{ClientMapper, ClientPlugin, ClientSet, LastTick, NetworkEntityMap},
+        clock_sync::{ClockSyncPlugin, ServerTime},
+        desync_detection::{ChecksumAppExt, DesyncDetected, DesyncDetectionPlugin},
         network_event::{
-            client_event::{ClientEventAppExt, FromClient},
+            client_event::{ClientEventAppExt, ClientViolation, FromClient, ViolationKind},
+            client_input::{ClientInput, ClientInputAppExt, InputSettings, MissingInput},
+            entity_event::{EntityEvent, EntityEventAppExt, ToEntity},
+            latest_event::{LatestEventAppExt, Superseding},
+            limits::{ClientEventLimitsAppExt, EventLimits, LimitPolicy},
+            lockstep::{LockstepAppExt, LockstepStep},
+            relayed_event::{FromPeer, RelayedEventAppExt, ToPeers},
+            request::{
+                ClientRequestAppExt, Request, RequestError, RequestId, RequestResult,
+                RequestSender, Response,
+            },
             server_event::{SendMode, ServerEventAppExt, ToClients},
-            BuildEventDeserializer, BuildEventSerializer, EventMapper, SendPolicy,
+            sticky_event::{Retention, StickyEventAppExt, StickyEvents},
+            tick_stamp::{ClientEventStampAppExt, ClientTick, InterpolationOffset},
+            validation::{ClientEventValidationAppExt, EntityOwner, EntityValidation},
+            BuildEventDeserializer, BuildEventSerializer, EventBatchingAppExt,
+            EventCompressionAppExt, EventMapper, EventMappingAppExt, EventMappingDiagnostics,
+            EventTimingAppExt, MappingPolicy, ReflectEventSerde, ReflectedEvent, SendPolicy,
+            SendTiming,
         },
         parent_sync::{ParentSync, ParentSyncPlugin},
         renet::{RenetClient, RenetServer},
         replicon_core::{
+            compression::{CompressionAlgorithm, NetworkCompression},
             replication_rules::{
                 AppReplicationExt, Ignored, MapNetworkEntities, Mapper, Replication,
                 ReplicationRules,
             },
+            snapshot::ReplicationSnapshot,
             NetworkChannels, NetworkTick, RepliconCorePlugin,
         },
-        server::{has_authority, AckedTicks, ServerPlugin, ServerSet, TickPolicy, SERVER_ID},
+        save::{
+            autosave::{Autosave, AutosavePlugin},
+            migration::{SaveMigrationAppExt, SaveMigrations},
+            SaveError, WorldSave,
+        },
+        server::{
+            has_authority,
+            history::{ComponentHistory, ComponentHistoryAppExt},
+            rooms::{RoomId, Rooms},
+            send_rate::{AdaptiveSendRate, ClientSendRates},
+            AckedTicks, ServerPlugin, ServerSet, TickPolicy, SERVER_ID,
+        },
         ReplicationPlugins,
     };
 }
@@ -418,5 +827,6 @@ impl PluginGroup for ReplicationPlugins {
             .add(ParentSyncPlugin)
             .add(ClientPlugin)
             .add(ServerPlugin::default())
+            .add(ClockSyncPlugin::default())
     }
 }
@@ -1,17 +1,158 @@
 pub mod client_event;
+pub mod client_input;
+pub mod entity_event;
+pub mod latest_event;
+pub mod limits;
+pub mod lockstep;
+pub mod relayed_event;
+pub mod request;
 pub mod server_event;
+pub mod sticky_event;
+pub mod tick_stamp;
+pub mod validation;
 
-use std::{marker::PhantomData, time::Duration};
+use std::{any, fmt::Debug, io::Cursor, marker::PhantomData, mem, time::Duration};
 
-use bevy::{prelude::*, reflect::TypeRegistryInternal, utils::HashMap};
-use bevy_renet::renet::SendType;
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::*,
+    reflect::{
+        serde::{ReflectSerializer, UntypedReflectDeserializer},
+        TypeRegistryInternal,
+    },
+    utils::HashMap,
+};
+use bevy_renet::renet::{Bytes, RenetClient, SendType};
+use bincode::{DefaultOptions, Options};
+use serde::{
+    de::{self, DeserializeSeed},
+    Deserializer,
+};
 
-use crate::replicon_core::replication_rules::Mapper;
+use crate::{
+    client::LastTick,
+    network_event::{tick_stamp::ClientTick, validation::EntityValidation},
+    replicon_core::{
+        compression::{self, NetworkCompression},
+        replication_rules::{MapNetworkEntities, Mapper},
+        NetworkTick,
+    },
+};
+
+/// An extension trait for [`App`] for enabling compression of network events.
+pub trait EventCompressionAppExt {
+    /// Compresses messages of already registered server or client event `T` using [`NetworkCompression`].
+    ///
+    /// Should be called on both server and clients.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered as server or client event.
+    fn compress_event<T: Event>(&mut self) -> &mut Self;
+}
+
+impl EventCompressionAppExt for App {
+    fn compress_event<T: Event>(&mut self) -> &mut Self {
+        event_channel_mut::<T>(self).compressed = true;
+
+        self
+    }
+}
+
+/// An extension trait for [`App`] for enabling batching of server events.
+pub trait EventBatchingAppExt {
+    /// Packs all events of already registered server event `T` for the same client
+    /// into a single message per tick instead of sending a message per event.
+    ///
+    /// Reduces overhead for events that are sent often. Should be called on both server and clients.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered as server event.
+    fn batch_server_event<T: Event>(&mut self) -> &mut Self;
+}
+
+impl EventBatchingAppExt for App {
+    fn batch_server_event<T: Event>(&mut self) -> &mut Self {
+        event_channel_mut::<T>(self).batched = true;
+
+        self
+    }
+}
+
+/// An extension trait for [`App`] for configuring when server events are sent.
+pub trait EventTimingAppExt {
+    /// Sets when already registered server event `T` should be sent to clients.
+    ///
+    /// Should be called on server.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered as server event.
+    fn set_send_timing<T: Event>(&mut self, timing: SendTiming) -> &mut Self;
+}
+
+impl EventTimingAppExt for App {
+    fn set_send_timing<T: Event>(&mut self, timing: SendTiming) -> &mut Self {
+        event_channel_mut::<T>(self).timing = timing;
+
+        self
+    }
+}
+
+/// An extension trait for [`App`] for configuring entity mapping of network events.
+pub trait EventMappingAppExt {
+    /// Sets what to do with already registered mapped server or client event `T`
+    /// if some of its entities can't be mapped.
+    ///
+    /// Should be called on clients since mapping happens only there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered as server or client event.
+    fn set_mapping_policy<T: Event>(&mut self, policy: MappingPolicy) -> &mut Self;
+}
+
+impl EventMappingAppExt for App {
+    fn set_mapping_policy<T: Event>(&mut self, policy: MappingPolicy) -> &mut Self {
+        event_channel_mut::<T>(self).mapping_policy = policy;
+
+        self
+    }
+}
+
+/// Returns channel of already registered network event `T` for configuration.
+///
+/// # Panics
+///
+/// Panics if `T` is not registered as network event.
+fn event_channel_mut<T: Event>(app: &mut App) -> Mut<'_, EventChannel<T>> {
+    app.world
+        .get_resource_mut::<EventChannel<T>>()
+        .unwrap_or_else(|| {
+            panic!(
+                "{} should be registered as network event",
+                any::type_name::<T>()
+            )
+        })
+}
 
 /// Holds a channel ID for `T`.
 #[derive(Resource)]
 pub struct EventChannel<T> {
     pub id: u8,
+    /// Whether messages should be compressed using [`NetworkCompression`].
+    compressed: bool,
+    /// Whether events should be sent in length-prefixed batches.
+    batched: bool,
+    /// When server events should be sent.
+    timing: SendTiming,
+    /// Whether client events should be prefixed with [`ClientTick`].
+    stamped: bool,
+    /// What to do with the event if its entities can't be mapped.
+    mapping_policy: MappingPolicy,
+    /// How to validate entities inside mapped client event on server.
+    validation: Option<EntityValidation>,
     marker: PhantomData<T>,
 }
 
@@ -19,9 +160,118 @@ impl<T> EventChannel<T> {
     fn new(id: u8) -> Self {
         Self {
             id,
+            compressed: false,
+            batched: false,
+            timing: Default::default(),
+            stamped: false,
+            mapping_policy: Default::default(),
+            validation: None,
             marker: PhantomData,
         }
     }
+
+    /// Compresses serialized event if compression is enabled for `T`.
+    fn pack(
+        &self,
+        compression: &NetworkCompression,
+        message: Vec<u8>,
+    ) -> Result<Bytes, bincode::Error> {
+        if self.compressed {
+            compression.compress(&message)
+        } else {
+            Ok(message.into())
+        }
+    }
+
+    /// Prefixes serialized event with its length if batching is enabled for `T`.
+    ///
+    /// Used to send a single event as a batch.
+    fn frame(&self, message: Vec<u8>) -> Result<Vec<u8>, bincode::Error> {
+        if self.batched {
+            let mut batch = Vec::with_capacity(message.len() + mem::size_of::<u64>());
+            write_batched(&mut batch, &message)?;
+            Ok(batch)
+        } else {
+            Ok(message)
+        }
+    }
+
+    /// Prefixes serialized event with the client tick if stamping is enabled for `T`.
+    fn stamp(&self, tick: ClientTick, message: Vec<u8>) -> Result<Vec<u8>, bincode::Error> {
+        if self.stamped {
+            let mut stamped = DefaultOptions::new().serialize(&tick)?;
+            stamped.extend(message);
+            Ok(stamped)
+        } else {
+            Ok(message)
+        }
+    }
+
+    /// Splits received message into the client tick and serialized event if stamping is enabled for `T`.
+    fn unstamp(&self, message: Bytes) -> Result<(Option<ClientTick>, Bytes), bincode::Error> {
+        if !self.stamped {
+            return Ok((None, message));
+        }
+
+        let mut cursor = Cursor::new(&*message);
+        let tick = DefaultOptions::new().deserialize_from(&mut cursor)?;
+        let message = message.slice(cursor.position() as usize..);
+
+        Ok((Some(tick), message))
+    }
+
+    /// Splits received message into serialized events if batching is enabled for `T`.
+    fn split(&self, message: Bytes) -> Result<Vec<Bytes>, bincode::Error> {
+        if !self.batched {
+            return Ok(vec![message]);
+        }
+
+        let mut messages = Vec::new();
+        let mut cursor = Cursor::new(&*message);
+        while cursor.position() < message.len() as u64 {
+            let len: usize = DefaultOptions::new().deserialize_from(&mut cursor)?;
+            let start = cursor.position() as usize;
+            let end = start
+                .checked_add(len)
+                .filter(|&end| end <= message.len())
+                .ok_or(bincode::ErrorKind::SizeLimit)?;
+            messages.push(message.slice(start..end));
+            cursor.set_position(end as u64);
+        }
+
+        Ok(messages)
+    }
+
+    /// Decompresses received message if compression is enabled for `T`.
+    fn unpack(
+        &self,
+        compression: &NetworkCompression,
+        message: Bytes,
+    ) -> Result<Bytes, bincode::Error> {
+        if self.compressed {
+            compression.decompress(message)
+        } else {
+            Ok(message)
+        }
+    }
+
+    /// Returns size of received message after [`Self::unpack`].
+    ///
+    /// For compressed messages the size is taken from the header, so it's known before decompression.
+    fn unpacked_len(&self, message: &[u8]) -> Option<usize> {
+        if self.compressed {
+            compression::decompressed_len(message)
+        } else {
+            Some(message.len())
+        }
+    }
+}
+
+/// Appends serialized event prefixed with its length to the batch.
+fn write_batched(batch: &mut Vec<u8>, message: &[u8]) -> Result<(), bincode::Error> {
+    DefaultOptions::new().serialize_into(&mut *batch, &message.len())?;
+    batch.extend_from_slice(message);
+    Ok(())
 }
 
 /// Creates a struct implements serialization for the event using [`TypeRegistryInternal`].
@@ -40,7 +290,82 @@ pub trait BuildEventDeserializer {
     fn new(registry: &TypeRegistryInternal) -> Self::EventDeserializer<'_>;
 }
 
+/// Event that can be converted to and from a reflected value.
+///
+/// Used by [`ReflectEventSerde`]. Implemented for all types that implement [`FromReflect`].
+/// Events that wrap `Box<dyn Reflect>` can implement it manually.
+pub trait ReflectedEvent: Sized {
+    fn as_reflect(&self) -> &dyn Reflect;
+
+    /// Creates the event from a deserialized value.
+    ///
+    /// Returns [`None`] if the value has a different type.
+    fn from_reflect(reflect: Box<dyn Reflect>) -> Option<Self>;
+}
+
+impl<T: FromReflect> ReflectedEvent for T {
+    fn as_reflect(&self) -> &dyn Reflect {
+        self
+    }
+
+    fn from_reflect(reflect: Box<dyn Reflect>) -> Option<Self> {
+        <T as FromReflect>::from_reflect(&*reflect)
+    }
+}
+
+/// Serializer and deserializer for events that implement [`ReflectedEvent`].
+///
+/// Serializes the reflected value with its type path, so the value type should be registered
+/// in [`AppTypeRegistry`] on both server and clients.
+pub struct ReflectEventSerde<T>(PhantomData<T>);
+
+impl<T: ReflectedEvent> BuildEventSerializer<T> for ReflectEventSerde<T> {
+    type EventSerializer<'a>
+        = ReflectSerializer<'a>
+    where
+        T: 'a;
+
+    fn new<'a>(event: &'a T, registry: &'a TypeRegistryInternal) -> Self::EventSerializer<'a> {
+        ReflectSerializer::new(event.as_reflect(), registry)
+    }
+}
+
+impl<T: ReflectedEvent> BuildEventDeserializer for ReflectEventSerde<T> {
+    type EventDeserializer<'a> = ReflectEventDeserializer<'a, T>;
+
+    fn new(registry: &TypeRegistryInternal) -> Self::EventDeserializer<'_> {
+        ReflectEventDeserializer {
+            registry,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Deserializer created by [`ReflectEventSerde`].
+pub struct ReflectEventDeserializer<'a, T> {
+    registry: &'a TypeRegistryInternal,
+    marker: PhantomData<T>,
+}
+
+impl<'de, T: ReflectedEvent> DeserializeSeed<'de> for ReflectEventDeserializer<'_, T> {
+    type Value = T;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let reflect = UntypedReflectDeserializer::new(self.registry).deserialize(deserializer)?;
+        let type_name = reflect.type_name().to_string();
+        T::from_reflect(reflect).ok_or_else(|| {
+            de::Error::custom(format!(
+                "{type_name} should be convertible into {}",
+                any::type_name::<T>()
+            ))
+        })
+    }
+}
+
 /// Event delivery guarantee.
+///
+/// For state-like server events where only the latest value matters,
+/// see [`LatestEventAppExt`](latest_event::LatestEventAppExt).
 #[derive(Clone, Copy, Debug)]
 pub enum SendPolicy {
     /// Unreliable and unordered
@@ -66,6 +391,192 @@ impl From<SendPolicy> for SendType {
     }
 }
 
+/// Defines when server events are sent to clients.
+///
+/// Events are never lost between server ticks regardless of the timing.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SendTiming {
+    /// Queue events and send them together with the next replication message (see [`TickPolicy`](crate::server::TickPolicy)).
+    ///
+    /// Entities spawned before sending a mapped event will be replicated
+    /// on the same tick on which the event is sent.
+    #[default]
+    NextTick,
+    /// Send events at the end of the frame in which they were sent.
+    ///
+    /// Reduces latency, but mapped events may arrive before their entities,
+    /// see [`MappingPolicy`].
+    Immediate,
+}
+
+/// Defines what to do with a mapped event if some of its entities don't have a mapping.
+///
+/// It may happen if the event arrived before the entity was replicated,
+/// after the entity was despawned or if the event refers to an entity that was never replicated.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum MappingPolicy {
+    /// Drop the event.
+    #[default]
+    Drop,
+    /// Replace unmapped entities with [`Entity::PLACEHOLDER`] and emit the event.
+    Placeholder,
+    /// Keep the event and retry mapping until the specified number of
+    /// server ticks will be received by the client, then drop it.
+    ///
+    /// Events received after a held event are held too to preserve the order.
+    Hold(u32),
+}
+
+/// Number of mapped events of type `T` that contained unmapped entities.
+///
+/// Available only on clients for mapped events.
+#[derive(Resource)]
+pub struct EventMappingDiagnostics<T> {
+    /// Events that were dropped.
+    pub dropped: usize,
+    /// Events that were emitted with [`Entity::PLACEHOLDER`].
+    pub placeholders: usize,
+    /// Events that were held to retry mapping later or to preserve the order.
+    ///
+    /// Held events will be counted again as dropped if the mapping still fails after the hold.
+    pub held: usize,
+    marker: PhantomData<T>,
+}
+
+impl<T> Default for EventMappingDiagnostics<T> {
+    fn default() -> Self {
+        Self {
+            dropped: 0,
+            placeholders: 0,
+            held: 0,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Events of type `T` held by [`MappingPolicy::Hold`] with the client tick at which they were held.
+#[derive(Resource)]
+struct HeldEvents<T>(Vec<(NetworkTick, T)>);
+
+impl<T> Default for HeldEvents<T> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+/// Initializes resources needed for mapping of event `T` on client.
+fn init_event_mapping<T: Event>(app: &mut App) {
+    app.init_resource::<HeldEvents<T>>()
+        .init_resource::<EventMappingDiagnostics<T>>()
+        .add_systems(
+            PostUpdate,
+            reset_held_system::<T>.run_if(resource_removed::<RenetClient>()),
+        );
+}
+
+fn reset_held_system<T: Event>(mut held_events: ResMut<HeldEvents<T>>) {
+    held_events.0.clear();
+}
+
+/// Maps entities inside events according to [`MappingPolicy`].
+#[derive(SystemParam)]
+struct EventMapping<'w, T: Event> {
+    held_events: ResMut<'w, HeldEvents<T>>,
+    diagnostics: ResMut<'w, EventMappingDiagnostics<T>>,
+    last_tick: Res<'w, LastTick>,
+}
+
+impl<T: Event + MapNetworkEntities + Debug> EventMapping<'_, T> {
+    /// Maps previously held events and then `events`.
+    ///
+    /// Returns events that should be emitted in the original order.
+    /// With [`MappingPolicy::Hold`] all events after an unmappable event are held too to preserve the order.
+    fn map(
+        &mut self,
+        events: impl IntoIterator<Item = T>,
+        entity_map: &HashMap<Entity, Entity>,
+        policy: MappingPolicy,
+    ) -> Vec<T> {
+        let tick = **self.last_tick;
+        let mut mapped_events = Vec::new();
+        let mut held_events = Vec::with_capacity(self.held_events.0.len());
+        for (held_tick, mut event) in self.held_events.0.drain(..) {
+            let mappable = is_mappable(&mut event, entity_map);
+            if mappable && held_events.is_empty() {
+                event.map_entities(&mut EventMapper(entity_map));
+                mapped_events.push(event);
+            } else if mappable
+                || matches!(policy, MappingPolicy::Hold(ticks) if tick.get().wrapping_sub(held_tick.get()) < ticks)
+            {
+                held_events.push((held_tick, event));
+            } else {
+                debug!("dropped held event {event:?} with unmapped entities");
+                self.diagnostics.dropped += 1;
+            }
+        }
+
+        for mut event in events {
+            if let MappingPolicy::Hold(_) = policy {
+                if !held_events.is_empty() || !is_mappable(&mut event, entity_map) {
+                    debug!("held event {event:?} to preserve order or map entities");
+                    self.diagnostics.held += 1;
+                    held_events.push((tick, event));
+                    continue;
+                }
+            }
+
+            let mut mapper = PlaceholderMapper::new(entity_map);
+            event.map_entities(&mut mapper);
+            if mapper.unmapped().is_empty() {
+                mapped_events.push(event);
+            } else if policy == MappingPolicy::Placeholder {
+                debug!(
+                    "replaced unmapped entities {:?} with placeholder in event {event:?}",
+                    mapper.unmapped()
+                );
+                self.diagnostics.placeholders += 1;
+                mapped_events.push(event);
+            } else {
+                debug!(
+                    "dropped event {event:?} with unmapped entities {:?}",
+                    mapper.unmapped()
+                );
+                self.diagnostics.dropped += 1;
+            }
+        }
+        self.held_events.0 = held_events;
+
+        mapped_events
+    }
+}
+
+/// Returns `true` if all entities inside the event have a mapping.
+///
+/// Doesn't modify the event.
+fn is_mappable<T: MapNetworkEntities>(event: &mut T, entity_map: &HashMap<Entity, Entity>) -> bool {
+    let mut checker = MappingChecker {
+        entity_map,
+        mappable: true,
+    };
+    event.map_entities(&mut checker);
+    checker.mappable
+}
+
+/// Checks if entities can be mapped without changing them.
+struct MappingChecker<'a> {
+    entity_map: &'a HashMap<Entity, Entity>,
+    mappable: bool,
+}
+
+impl Mapper for MappingChecker<'_> {
+    fn map(&mut self, entity: Entity) -> Entity {
+        if !self.entity_map.contains_key(&entity) {
+            self.mappable = false;
+        }
+        entity
+    }
+}
+
 /// Maps server entities into client entities inside events.
 ///
 /// Panics if a mapping doesn't exists.
@@ -79,3 +590,34 @@ impl Mapper for EventMapper<'_> {
             .unwrap_or_else(|| panic!("entity {entity:?} should be mappable"))
     }
 }
+
+/// Maps entities inside events using the specified map.
+///
+/// Entities without a mapping are replaced with [`Entity::PLACEHOLDER`] and reported in [`Self::unmapped`].
+struct PlaceholderMapper<'a> {
+    entity_map: &'a HashMap<Entity, Entity>,
+    unmapped: Vec<Entity>,
+}
+
+impl<'a> PlaceholderMapper<'a> {
+    fn new(entity_map: &'a HashMap<Entity, Entity>) -> Self {
+        Self {
+            entity_map,
+            unmapped: Default::default(),
+        }
+    }
+
+    /// Returns entities that had no mapping.
+    fn unmapped(&self) -> &[Entity] {
+        &self.unmapped
+    }
+}
+
+impl Mapper for PlaceholderMapper<'_> {
+    fn map(&mut self, entity: Entity) -> Entity {
+        self.entity_map.get(&entity).copied().unwrap_or_else(|| {
+            self.unmapped.push(entity);
+            Entity::PLACEHOLDER
+        })
+    }
+}
@@ -1,8 +1,8 @@
 use std::fmt::Debug;
 
-use bevy::{ecs::event::Event, prelude::*};
+use bevy::{ecs::event::Event, prelude::*, reflect::GetTypeRegistration};
 use bevy_renet::{
-    renet::{RenetClient, RenetServer, SendType},
+    renet::{Bytes, RenetClient, RenetServer, SendType},
     transport::client_connected,
 };
 use bincode::{DefaultOptions, Options};
@@ -11,11 +11,19 @@ use serde::{
     Serialize,
 };
 
-use super::{BuildEventDeserializer, BuildEventSerializer, EventChannel};
+use super::{
+    init_event_mapping,
+    limits::ClientMessages,
+    tick_stamp::{ClientTick, TickStamper},
+    validation::EntityValidator,
+    BuildEventDeserializer, BuildEventSerializer, EventChannel, EventMapping, ReflectEventSerde,
+};
 use crate::{
     client::{ClientSet, NetworkEntityMap},
-    network_event::EventMapper,
-    replicon_core::{replication_rules::MapNetworkEntities, NetworkChannels},
+    replicon_core::{
+        compression::NetworkCompression, replication_rules::MapNetworkEntities, NetworkChannels,
+        NetworkTick,
+    },
     server::{has_authority, ServerSet, SERVER_ID},
 };
 
@@ -28,6 +36,10 @@ pub trait ClientEventAppExt {
     ) -> &mut Self;
 
     /// Same as [`Self::add_client_event`], but additionally maps client entities to server before sending.
+    ///
+    /// Events with entities that can't be mapped are handled according to [`MappingPolicy`](super::MappingPolicy).
+    /// Received entities can be validated on server using
+    /// [`ClientEventValidationAppExt`](super::validation::ClientEventValidationAppExt).
     fn add_mapped_client_event<
         T: Event + Serialize + DeserializeOwned + Debug + MapNetworkEntities,
     >(
@@ -47,7 +59,16 @@ pub trait ClientEventAppExt {
         for<'a> S::EventSerializer<'a>: Serialize,
         for<'a, 'de> D::EventDeserializer<'a>: DeserializeSeed<'de, Value = T>;
 
+    /// Same as [`Self::add_client_reflect_event`], but uses [`ReflectEventSerde`] and registers `T` in [`AppTypeRegistry`].
+    fn add_client_event_reflect<T>(&mut self, policy: impl Into<SendType>) -> &mut Self
+    where
+        T: Event + Debug + FromReflect + GetTypeRegistration;
+
     /// Same as [`Self::add_client_reflect_event`], but additionally maps client entities to server before sending.
+    ///
+    /// Events with entities that can't be mapped are handled according to [`MappingPolicy`](super::MappingPolicy).
+    /// Received entities can be validated on server using
+    /// [`ClientEventValidationAppExt`](super::validation::ClientEventValidationAppExt).
     fn add_mapped_client_reflect_event<T, S, D>(
         &mut self,
         policy: impl Into<SendType>,
@@ -85,8 +106,10 @@ impl ClientEventAppExt for App {
         self.add_client_event_with::<T, _, _>(
             policy,
             mapping_and_sending_system::<T>,
-            receiving_system::<T>,
-        )
+            receiving_and_validating_system::<T>,
+        );
+        init_event_mapping::<T>(self);
+        self
     }
 
     fn add_client_reflect_event<T, S, D>(&mut self, policy: impl Into<SendType>) -> &mut Self
@@ -104,6 +127,14 @@ impl ClientEventAppExt for App {
         )
     }
 
+    fn add_client_event_reflect<T>(&mut self, policy: impl Into<SendType>) -> &mut Self
+    where
+        T: Event + Debug + FromReflect + GetTypeRegistration,
+    {
+        self.register_type::<T>()
+            .add_client_reflect_event::<T, ReflectEventSerde<T>, ReflectEventSerde<T>>(policy)
+    }
+
     fn add_mapped_client_reflect_event<T, S, D>(&mut self, policy: impl Into<SendType>) -> &mut Self
     where
         T: Event + Debug + MapNetworkEntities,
@@ -115,8 +146,10 @@ impl ClientEventAppExt for App {
         self.add_client_event_with::<T, _, _>(
             policy,
             mapping_and_sending_reflect_system::<T, S>,
-            receiving_reflect_system::<T, D>,
-        )
+            receiving_and_validating_reflect_system::<T, D>,
+        );
+        init_event_mapping::<T>(self);
+        self
     }
 
     fn add_client_event_with<T: Event + Debug, Marker1, Marker2>(
@@ -131,6 +164,7 @@ impl ClientEventAppExt for App {
             .create_client_channel(policy.into());
 
         self.add_event::<T>()
+            .add_event::<ClientViolation>()
             .init_resource::<Events<FromClient<T>>>()
             .insert_resource(EventChannel::<T>::new(channel_id))
             .add_systems(
@@ -156,16 +190,22 @@ impl ClientEventAppExt for App {
 fn receiving_system<T: Event + DeserializeOwned + Debug>(
     mut client_events: EventWriter<FromClient<T>>,
     mut server: ResMut<RenetServer>,
+    mut messages: ClientMessages<T>,
     channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
 ) {
     for client_id in server.clients_id() {
-        while let Some(message) = server.receive_message(client_id, channel.id) {
-            match DefaultOptions::new().deserialize(&message) {
-                Ok(event) => {
-                    debug!("received event {event:?} from client {client_id}");
-                    client_events.send(FromClient { client_id, event });
-                }
-                Err(e) => error!("unable to deserialize event from client {client_id}: {e}"),
+        while let Some(message) = messages.receive(&mut server, client_id, &channel) {
+            if let Some((tick, event)) =
+                decode_event(&channel, &compression, client_id, message, |message| {
+                    DefaultOptions::new().deserialize(message)
+                })
+            {
+                client_events.send(FromClient {
+                    client_id,
+                    tick,
+                    event,
+                });
             }
         }
     }
@@ -174,7 +214,9 @@ fn receiving_system<T: Event + DeserializeOwned + Debug>(
 fn receiving_reflect_system<T, D>(
     mut client_events: EventWriter<FromClient<T>>,
     mut server: ResMut<RenetServer>,
+    mut messages: ClientMessages<T>,
     channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
     registry: Res<AppTypeRegistry>,
 ) where
     T: Event + Debug,
@@ -183,31 +225,141 @@ fn receiving_reflect_system<T, D>(
 {
     let registry = registry.read();
     for client_id in server.clients_id() {
-        while let Some(message) = server.receive_message(client_id, channel.id) {
-            let mut deserializer =
-                bincode::Deserializer::from_slice(&message, DefaultOptions::new());
-            match D::new(&registry).deserialize(&mut deserializer) {
-                Ok(event) => {
-                    debug!("received reflect event {event:?} from client {client_id}");
-                    client_events.send(FromClient { client_id, event });
+        while let Some(message) = messages.receive(&mut server, client_id, &channel) {
+            if let Some((tick, event)) =
+                decode_event(&channel, &compression, client_id, message, |message| {
+                    let mut deserializer =
+                        bincode::Deserializer::from_slice(message, DefaultOptions::new());
+                    D::new(&registry).deserialize(&mut deserializer)
+                })
+            {
+                client_events.send(FromClient {
+                    client_id,
+                    tick,
+                    event,
+                });
+            }
+        }
+    }
+}
+
+fn receiving_and_validating_system<T: Event + MapNetworkEntities + DeserializeOwned + Debug>(
+    mut client_events: EventWriter<FromClient<T>>,
+    mut server: ResMut<RenetServer>,
+    mut messages: ClientMessages<T>,
+    validator: EntityValidator,
+    channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
+) {
+    for client_id in server.clients_id() {
+        while let Some(message) = messages.receive(&mut server, client_id, &channel) {
+            if let Some((tick, mut event)) =
+                decode_event(&channel, &compression, client_id, message, |message| {
+                    DefaultOptions::new().deserialize(message)
+                })
+            {
+                match validator.validate(client_id, &mut event, channel.validation) {
+                    Ok(()) => client_events.send(FromClient {
+                        client_id,
+                        tick,
+                        event,
+                    }),
+                    Err(kind) => messages.report(client_id, kind),
                 }
-                Err(e) => {
-                    error!("unable to deserialize reflect event from client {client_id}: {e}")
+            }
+        }
+    }
+}
+
+fn receiving_and_validating_reflect_system<T, D>(
+    mut client_events: EventWriter<FromClient<T>>,
+    mut server: ResMut<RenetServer>,
+    mut messages: ClientMessages<T>,
+    validator: EntityValidator,
+    channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
+    registry: Res<AppTypeRegistry>,
+) where
+    T: Event + MapNetworkEntities + Debug,
+    D: BuildEventDeserializer,
+    for<'a, 'de> D::EventDeserializer<'a>: DeserializeSeed<'de, Value = T>,
+{
+    let registry = registry.read();
+    for client_id in server.clients_id() {
+        while let Some(message) = messages.receive(&mut server, client_id, &channel) {
+            if let Some((tick, mut event)) =
+                decode_event(&channel, &compression, client_id, message, |message| {
+                    let mut deserializer =
+                        bincode::Deserializer::from_slice(message, DefaultOptions::new());
+                    D::new(&registry).deserialize(&mut deserializer)
+                })
+            {
+                match validator.validate(client_id, &mut event, channel.validation) {
+                    Ok(()) => client_events.send(FromClient {
+                        client_id,
+                        tick,
+                        event,
+                    }),
+                    Err(kind) => messages.report(client_id, kind),
                 }
             }
         }
     }
 }
 
+/// Decompresses the message from a client, reads its tick stamp and deserializes the event using `deserialize`.
+///
+/// Logs and returns [`None`] on any error.
+fn decode_event<T: Debug>(
+    channel: &EventChannel<T>,
+    compression: &NetworkCompression,
+    client_id: u64,
+    message: Bytes,
+    deserialize: impl FnOnce(&[u8]) -> bincode::Result<T>,
+) -> Option<(Option<ClientTick>, T)> {
+    let message = match channel.unpack(compression, message) {
+        Ok(message) => message,
+        Err(e) => {
+            error!("unable to decompress event from client {client_id}: {e}");
+            return None;
+        }
+    };
+    let (tick, message) = match channel.unstamp(message) {
+        Ok(stamped) => stamped,
+        Err(e) => {
+            error!("unable to read tick of event from client {client_id}: {e}");
+            return None;
+        }
+    };
+    let event = match deserialize(&message) {
+        Ok(event) => event,
+        Err(e) => {
+            error!("unable to deserialize event from client {client_id}: {e}");
+            return None;
+        }
+    };
+    debug!("received event {event:?} from client {client_id}");
+
+    Some((tick, event))
+}
+
 fn sending_system<T: Event + Serialize + Debug>(
     mut events: EventReader<T>,
     mut client: ResMut<RenetClient>,
     channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
+    stamper: TickStamper,
 ) {
     for event in &mut events {
         let message = DefaultOptions::new()
             .serialize(&event)
             .expect("client event should be serializable");
+        let message = channel
+            .stamp(stamper.client_tick(), message)
+            .expect("client tick should be serializable");
+        let message = channel
+            .pack(&compression, message)
+            .expect("client event should be compressible");
         client.send_message(channel.id, message);
         debug!("sent client event {event:?}");
     }
@@ -216,14 +368,26 @@ fn sending_system<T: Event + Serialize + Debug>(
 fn mapping_and_sending_system<T: Event + MapNetworkEntities + Serialize + Debug>(
     mut events: ResMut<Events<T>>,
     mut client: ResMut<RenetClient>,
+    mut event_mapping: EventMapping<T>,
     entity_map: Res<NetworkEntityMap>,
     channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
+    stamper: TickStamper,
 ) {
-    for mut event in events.drain() {
-        event.map_entities(&mut EventMapper(entity_map.to_server()));
+    for event in event_mapping.map(
+        events.drain(),
+        entity_map.to_server(),
+        channel.mapping_policy,
+    ) {
         let message = DefaultOptions::new()
             .serialize(&event)
             .expect("mapped client event should be serializable");
+        let message = channel
+            .stamp(stamper.client_tick(), message)
+            .expect("client tick should be serializable");
+        let message = channel
+            .pack(&compression, message)
+            .expect("mapped client event should be compressible");
         client.send_message(channel.id, message);
         debug!("sent mapped client event {event:?}");
     }
@@ -233,6 +397,8 @@ fn sending_reflect_system<T, S>(
     mut events: EventReader<T>,
     mut client: ResMut<RenetClient>,
     channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
+    stamper: TickStamper,
     registry: Res<AppTypeRegistry>,
 ) where
     T: Event + Debug,
@@ -245,16 +411,26 @@ fn sending_reflect_system<T, S>(
         let message = DefaultOptions::new()
             .serialize(&serializer)
             .expect("client reflect event should be serializable");
+        let message = channel
+            .stamp(stamper.client_tick(), message)
+            .expect("client tick should be serializable");
+        let message = channel
+            .pack(&compression, message)
+            .expect("client reflect event should be compressible");
         client.send_message(channel.id, message);
         debug!("sent client reflect event {event:?}");
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn mapping_and_sending_reflect_system<T, S>(
     mut events: ResMut<Events<T>>,
     mut client: ResMut<RenetClient>,
+    mut event_mapping: EventMapping<T>,
     entity_map: Res<NetworkEntityMap>,
     channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
+    stamper: TickStamper,
     registry: Res<AppTypeRegistry>,
 ) where
     T: Event + MapNetworkEntities + Debug,
@@ -262,12 +438,21 @@ fn mapping_and_sending_reflect_system<T, S>(
     for<'a> S::EventSerializer<'a>: Serialize,
 {
     let registry = registry.read();
-    for mut event in events.drain() {
-        event.map_entities(&mut EventMapper(entity_map.to_server()));
+    for event in event_mapping.map(
+        events.drain(),
+        entity_map.to_server(),
+        channel.mapping_policy,
+    ) {
         let serializer = S::new(&event, &registry);
         let message = DefaultOptions::new()
             .serialize(&serializer)
             .expect("mapped client reflect event should be serializable");
+        let message = channel
+            .stamp(stamper.client_tick(), message)
+            .expect("client tick should be serializable");
+        let message = channel
+            .pack(&compression, message)
+            .expect("mapped client reflect event should be compressible");
         client.send_message(channel.id, message);
         debug!("sent mapped client reflect event {event:?}");
     }
@@ -278,11 +463,14 @@ fn mapping_and_sending_reflect_system<T, S>(
 fn local_resending_system<T: Event + Debug>(
     mut events: ResMut<Events<T>>,
     mut client_events: EventWriter<FromClient<T>>,
+    channel: Res<EventChannel<T>>,
+    stamper: TickStamper,
 ) {
     for event in events.drain() {
         debug!("converted client event {event:?} into a local");
         client_events.send(FromClient {
             client_id: SERVER_ID,
+            tick: channel.stamped.then(|| stamper.local_tick()),
             event,
         })
     }
@@ -293,5 +481,45 @@ fn local_resending_system<T: Event + Debug>(
 #[derive(Clone, Copy, Event)]
 pub struct FromClient<T> {
     pub client_id: u64,
+    /// Server tick that the client was displaying when it sent the event.
+    ///
+    /// Available only if the event is stamped, see [`ClientEventStampAppExt`](super::tick_stamp::ClientEventStampAppExt).
+    pub tick: Option<ClientTick>,
     pub event: T,
 }
+
+/// An event indicating that a client sent an invalid event.
+///
+/// Emitted only on server. Can be used to detect cheating clients.
+#[derive(Clone, Debug, Event)]
+pub struct ClientViolation {
+    pub client_id: u64,
+    /// Type name of the rejected event.
+    pub event_name: &'static str,
+    pub kind: ViolationKind,
+}
+
+/// Reason of [`ClientViolation`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ViolationKind {
+    /// Event refers to an entity that is not replicated.
+    NotReplicated(Entity),
+    /// Event refers to an entity that is not visible to the client.
+    NotVisible(Entity),
+    /// Event refers to an entity that is not owned by the client.
+    NotOwned(Entity),
+    /// Client sent messages faster than allowed by [`EventLimits`](super::limits::EventLimits).
+    RateLimitExceeded {
+        /// Number of messages that exceeded the rate in this frame.
+        count: usize,
+    },
+    /// Client sent messages that exceed the size allowed by [`EventLimits`](super::limits::EventLimits).
+    SizeLimitExceeded {
+        /// Size of the largest message.
+        size: usize,
+        /// Number of messages that exceeded the size in this frame.
+        count: usize,
+    },
+    /// Client submitted lockstep commands for a step too far ahead of the server.
+    StepTooFarAhead(NetworkTick),
+}
@@ -0,0 +1,389 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Debug,
+};
+
+use bevy::{ecs::event::Event, prelude::*, utils::HashMap};
+use bevy_renet::{
+    renet::{RenetClient, RenetServer, SendType, ServerEvent},
+    transport::client_connected,
+};
+use bincode::{DefaultOptions, Options};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    client_event::ClientViolation,
+    limits::ClientMessages,
+    tick_stamp::{ClientTick, TickStamper},
+    EventChannel,
+};
+use crate::{
+    client::ClientSet,
+    replicon_core::{compression::NetworkCompression, NetworkChannels, NetworkTick},
+    server::{has_authority, ServerSet, SERVER_ID},
+};
+
+/// An extension trait for [`App`] for replicating player input.
+pub trait ClientInputAppExt {
+    /// Registers [`ClientInput<I>`] event that will be emitted on server after sending `I` event on client.
+    ///
+    /// Each input is numbered and stamped with [`ClientTick`] on client and sent unreliably together with the previous inputs
+    /// (see [`InputSettings::redundancy`]), so lost packets don't lose inputs and there is no
+    /// head-of-line blocking. Server keeps received inputs in a per-client jitter buffer
+    /// and releases exactly one input per server tick.
+    ///
+    /// Messages from clients can be limited using
+    /// [`ClientEventLimitsAppExt`](super::limits::ClientEventLimitsAppExt) for `I`.
+    fn add_client_input<I: Event + Clone + Serialize + DeserializeOwned + Debug>(
+        &mut self,
+        settings: InputSettings,
+    ) -> &mut Self;
+}
+
+impl ClientInputAppExt for App {
+    fn add_client_input<I: Event + Clone + Serialize + DeserializeOwned + Debug>(
+        &mut self,
+        settings: InputSettings,
+    ) -> &mut Self {
+        let channel_id = self
+            .world
+            .resource_mut::<NetworkChannels>()
+            .create_client_channel(SendType::Unreliable);
+
+        self.add_event::<I>()
+            .add_event::<ClientInput<I>>()
+            .add_event::<ClientViolation>()
+            .insert_resource(EventChannel::<I>::new(channel_id))
+            .insert_resource(InputHistory::<I>::new(settings.redundancy))
+            .insert_resource(InputBuffers::<I>::new(settings))
+            .add_systems(
+                PreUpdate,
+                (receiving_system::<I>, releasing_system::<I>)
+                    .chain()
+                    .in_set(ServerSet::Receive)
+                    .run_if(resource_exists::<RenetServer>()),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    (
+                        sending_system::<I>.run_if(client_connected()),
+                        local_resending_system::<I>.run_if(has_authority()),
+                    )
+                        .chain()
+                        .in_set(ClientSet::Send),
+                    client_reset_system::<I>.run_if(resource_removed::<RenetClient>()),
+                    server_reset_system::<I>.run_if(resource_removed::<RenetServer>()),
+                ),
+            )
+    }
+}
+
+fn receiving_system<I: Event + Clone + DeserializeOwned + Debug>(
+    mut server_events: EventReader<ServerEvent>,
+    mut server: ResMut<RenetServer>,
+    mut messages: ClientMessages<I>,
+    mut buffers: ResMut<InputBuffers<I>>,
+    channel: Res<EventChannel<I>>,
+    compression: Res<NetworkCompression>,
+) {
+    for event in &mut server_events {
+        if let ServerEvent::ClientDisconnected { client_id, .. } = event {
+            buffers.clients.remove(client_id);
+        }
+    }
+
+    let settings = buffers.settings;
+    for client_id in server.clients_id() {
+        while let Some(message) = messages.receive(&mut server, client_id, &channel) {
+            let message = match channel.unpack(&compression, message) {
+                Ok(message) => message,
+                Err(e) => {
+                    error!("unable to decompress input from client {client_id}: {e}");
+                    continue;
+                }
+            };
+            match DefaultOptions::new().deserialize::<(u64, Vec<(ClientTick, I)>)>(&message) {
+                Ok((first_sequence, inputs)) => {
+                    let buffer = buffers.clients.entry(client_id).or_default();
+                    for (index, (tick, input)) in inputs.into_iter().enumerate() {
+                        let sequence = first_sequence.wrapping_add(index as u64);
+                        buffer.insert(sequence, tick, input, settings);
+                    }
+                }
+                Err(e) => error!("unable to deserialize input from client {client_id}: {e}"),
+            }
+        }
+    }
+}
+
+/// Releases one buffered input for each client on server tick.
+fn releasing_system<I: Event + Clone + Debug>(
+    mut inputs: EventWriter<ClientInput<I>>,
+    mut buffers: ResMut<InputBuffers<I>>,
+    network_tick: Res<NetworkTick>,
+) {
+    if !network_tick.is_changed() {
+        return;
+    }
+
+    let settings = buffers.settings;
+    for (&client_id, buffer) in &mut buffers.clients {
+        if let Some(input) = buffer.release(client_id, settings) {
+            debug!("released input {input:?}");
+            inputs.send(input);
+        }
+    }
+}
+
+/// Numbers and stamps inputs and sends them with the previous inputs.
+fn sending_system<I: Event + Clone + Serialize + Debug>(
+    mut inputs: EventReader<I>,
+    mut client: ResMut<RenetClient>,
+    mut history: ResMut<InputHistory<I>>,
+    channel: Res<EventChannel<I>>,
+    compression: Res<NetworkCompression>,
+    stamper: TickStamper,
+) {
+    let mut changed = false;
+    for input in &mut inputs {
+        history.push(stamper.client_tick(), input.clone());
+        changed = true;
+    }
+
+    if changed {
+        let message = DefaultOptions::new()
+            .serialize(&(history.first_sequence(), &history.inputs))
+            .expect("client input should be serializable");
+        let message = channel
+            .pack(&compression, message)
+            .expect("client input should be compressible");
+        client.send_message(channel.id, message);
+        debug!(
+            "sent inputs up to {} with redundancy",
+            history.next_sequence.wrapping_sub(1)
+        );
+    }
+}
+
+/// Transforms `I` events into [`ClientInput<I>`] events to "emulate"
+/// message sending for offline mode or when server is also a player
+fn local_resending_system<I: Event + Debug>(
+    mut inputs: ResMut<Events<I>>,
+    mut client_inputs: EventWriter<ClientInput<I>>,
+    mut history: ResMut<InputHistory<I>>,
+    stamper: TickStamper,
+) {
+    for input in inputs.drain() {
+        debug!("converted input {input:?} into a local");
+        client_inputs.send(ClientInput {
+            client_id: SERVER_ID,
+            sequence: history.next_sequence,
+            tick: stamper.local_tick(),
+            input,
+            repeated: false,
+        });
+        history.next_sequence = history.next_sequence.wrapping_add(1);
+    }
+}
+
+fn client_reset_system<I: Event>(mut history: ResMut<InputHistory<I>>) {
+    history.next_sequence = 0;
+    history.inputs.clear();
+}
+
+fn server_reset_system<I: Event>(mut buffers: ResMut<InputBuffers<I>>) {
+    buffers.clients.clear();
+}
+
+/// Settings for [`ClientInputAppExt::add_client_input`].
+#[derive(Clone, Copy, Debug)]
+pub struct InputSettings {
+    /// Number of the last inputs included into each message.
+    pub redundancy: usize,
+
+    /// Number of inputs that server buffers for a client before it starts releasing them.
+    ///
+    /// Higher values tolerate more jitter, but add latency.
+    pub buffer_delay: usize,
+
+    /// Maximum number of buffered inputs for a client.
+    ///
+    /// If the client sends inputs faster than server ticks, the oldest inputs are dropped.
+    pub max_buffered: usize,
+
+    /// What to do if the next input is missing on server tick.
+    pub missing: MissingInput,
+}
+
+impl Default for InputSettings {
+    fn default() -> Self {
+        Self {
+            redundancy: 4,
+            buffer_delay: 2,
+            max_buffered: 16,
+            missing: Default::default(),
+        }
+    }
+}
+
+/// Defines what server does if the next client input is missing on server tick.
+///
+/// If newer inputs are already received, the missing input is considered lost and skipped after applying the policy.
+/// Otherwise the client is considered late and the input is expected to arrive later.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum MissingInput {
+    /// Don't emit anything for this tick.
+    #[default]
+    Skip,
+    /// Emit the last released input with [`ClientInput::repeated`] set to `true`.
+    RepeatLast,
+    /// Don't emit anything and wait for the missing input even if newer inputs are received.
+    ///
+    /// Inputs are still skipped when [`InputSettings::max_buffered`] is exceeded.
+    Wait,
+}
+
+/// Input of a client released from server-side buffer.
+///
+/// Emitted only on server, one per server tick for each client.
+#[derive(Clone, Copy, Debug, Event)]
+pub struct ClientInput<I> {
+    pub client_id: u64,
+    /// Number of the input assigned by client.
+    ///
+    /// 64-bit, so it never wraps around in practice and inputs can be ordered by it.
+    pub sequence: u64,
+    /// Tick of the client when it sent the input.
+    ///
+    /// Useful for lag compensation together with [`ComponentHistory`](crate::server::history::ComponentHistory).
+    pub tick: ClientTick,
+    pub input: I,
+    /// Whether the input was missing and the last input was repeated instead, see [`MissingInput::RepeatLast`].
+    pub repeated: bool,
+}
+
+/// Last sent inputs.
+///
+/// Used only on client.
+#[derive(Resource)]
+struct InputHistory<I> {
+    /// Number that will be assigned to the next input.
+    next_sequence: u64,
+    inputs: VecDeque<(ClientTick, I)>,
+    redundancy: usize,
+}
+
+impl<I> InputHistory<I> {
+    fn new(redundancy: usize) -> Self {
+        Self {
+            next_sequence: 0,
+            inputs: Default::default(),
+            redundancy,
+        }
+    }
+
+    fn push(&mut self, tick: ClientTick, input: I) {
+        if self.inputs.len() == self.redundancy.max(1) {
+            self.inputs.pop_front();
+        }
+        self.inputs.push_back((tick, input));
+        self.next_sequence = self.next_sequence.wrapping_add(1);
+    }
+
+    /// Returns the number of the oldest stored input.
+    fn first_sequence(&self) -> u64 {
+        self.next_sequence.wrapping_sub(self.inputs.len() as u64)
+    }
+}
+
+/// Jitter buffers for each client.
+///
+/// Used only on server.
+#[derive(Resource)]
+struct InputBuffers<I> {
+    settings: InputSettings,
+    clients: HashMap<u64, InputBuffer<I>>,
+}
+
+impl<I> InputBuffers<I> {
+    fn new(settings: InputSettings) -> Self {
+        Self {
+            settings,
+            clients: Default::default(),
+        }
+    }
+}
+
+struct InputBuffer<I> {
+    /// Number of the next input to release.
+    ///
+    /// [`None`] until [`InputSettings::buffer_delay`] inputs will be buffered.
+    next_sequence: Option<u64>,
+    inputs: BTreeMap<u64, (ClientTick, I)>,
+    last: Option<(ClientTick, I)>,
+}
+
+impl<I> Default for InputBuffer<I> {
+    fn default() -> Self {
+        Self {
+            next_sequence: None,
+            inputs: Default::default(),
+            last: None,
+        }
+    }
+}
+
+impl<I: Clone> InputBuffer<I> {
+    fn insert(&mut self, sequence: u64, tick: ClientTick, input: I, settings: InputSettings) {
+        if self
+            .next_sequence
+            .is_some_and(|next_sequence| sequence < next_sequence)
+        {
+            // Already released or skipped.
+            return;
+        }
+
+        self.inputs.entry(sequence).or_insert((tick, input));
+        if self.inputs.len() > settings.max_buffered {
+            self.inputs.pop_first();
+            self.next_sequence = self.inputs.keys().next().copied();
+        }
+
+        if self.next_sequence.is_none() && self.inputs.len() >= settings.buffer_delay {
+            self.next_sequence = self.inputs.keys().next().copied();
+        }
+    }
+
+    fn release(&mut self, client_id: u64, settings: InputSettings) -> Option<ClientInput<I>> {
+        let sequence = self.next_sequence?;
+        if let Some((tick, input)) = self.inputs.remove(&sequence) {
+            self.next_sequence = Some(sequence.wrapping_add(1));
+            self.last = Some((tick, input.clone()));
+            return Some(ClientInput {
+                client_id,
+                sequence,
+                tick,
+                input,
+                repeated: false,
+            });
+        }
+
+        let lost = !self.inputs.is_empty();
+        if lost && settings.missing != MissingInput::Wait {
+            self.next_sequence = Some(sequence.wrapping_add(1));
+        }
+
+        if settings.missing == MissingInput::RepeatLast {
+            self.last.clone().map(|(tick, input)| ClientInput {
+                client_id,
+                sequence,
+                tick,
+                input,
+                repeated: true,
+            })
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt::Debug;
+
+use bevy::{ecs::event::Event, prelude::*};
+use bevy_renet::{
+    renet::{RenetClient, RenetServer, SendType},
+    transport::client_connected,
+};
+use bincode::{DefaultOptions, Options};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::EventChannel;
+use crate::{
+    client::{ClientSet, LastTick, NetworkEntityMap},
+    replicon_core::{
+        compression::NetworkCompression, replication_rules::Replication, NetworkChannels,
+        NetworkTick,
+    },
+    server::{
+        has_authority,
+        rooms::{RoomId, Rooms},
+        ServerSet, SERVER_ID,
+    },
+};
+
+/// An extension trait for [`App`] for creating server events about specific entities.
+pub trait EntityEventAppExt {
+    /// Registers event `T` that will be emitted on client as [`EntityEvent<T>`] after sending [`ToEntity<T>`] on server.
+    ///
+    /// The event will be sent only to clients that replicate the entity (see [`Rooms`]).
+    /// On client the entity will be mapped to the client entity. If the client doesn't know
+    /// the entity yet, the event will be buffered until the spawn arrives. If the client received
+    /// the server state for the event's tick and still doesn't know the entity, the event will be dropped.
+    fn add_server_entity_event<T: Event + Serialize + DeserializeOwned + Debug>(
+        &mut self,
+        policy: impl Into<SendType>,
+    ) -> &mut Self;
+}
+
+impl EntityEventAppExt for App {
+    fn add_server_entity_event<T: Event + Serialize + DeserializeOwned + Debug>(
+        &mut self,
+        policy: impl Into<SendType>,
+    ) -> &mut Self {
+        let channel_id = self
+            .world
+            .resource_mut::<NetworkChannels>()
+            .create_server_channel(policy.into());
+
+        self.add_event::<EntityEvent<T>>()
+            .add_event::<ToEntity<T>>()
+            .init_resource::<EntityEventBuffer<T>>()
+            .insert_resource(EventChannel::<ToEntity<T>>::new(channel_id))
+            .add_systems(
+                PreUpdate,
+                (
+                    receiving_system::<T>
+                        .in_set(ClientSet::Receive)
+                        .run_if(client_connected()),
+                    mapping_system::<T>
+                        .after(ClientSet::Receive)
+                        .run_if(resource_exists::<RenetClient>()),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    (
+                        sending_system::<T>.run_if(resource_exists::<RenetServer>()),
+                        local_resending_system::<T>.run_if(has_authority()),
+                    )
+                        .chain()
+                        .in_set(ServerSet::SendEvents),
+                    reset_system::<T>.run_if(resource_removed::<RenetClient>()),
+                ),
+            );
+
+        self
+    }
+}
+
+fn receiving_system<T: Event + DeserializeOwned + Debug>(
+    mut buffer: ResMut<EntityEventBuffer<T>>,
+    mut client: ResMut<RenetClient>,
+    channel: Res<EventChannel<ToEntity<T>>>,
+    compression: Res<NetworkCompression>,
+) {
+    while let Some(message) = client.receive_message(channel.id) {
+        let message = channel
+            .unpack(&compression, message)
+            .expect("server should send valid compressed events");
+        let event: EntityEventMessage<T> = DefaultOptions::new()
+            .deserialize(&message)
+            .expect("server should send valid entity events");
+        debug!("received entity event {event:?} from server");
+        buffer.0.push(event);
+    }
+}
+
+/// Emits buffered events for entities that are known to the client.
+fn mapping_system<T: Event + Debug>(
+    mut entity_events: EventWriter<EntityEvent<T>>,
+    mut buffer: ResMut<EntityEventBuffer<T>>,
+    entity_map: Res<NetworkEntityMap>,
+    last_tick: Res<LastTick>,
+) {
+    let mut events = Vec::with_capacity(buffer.0.len());
+    for event in buffer.0.drain(..) {
+        if let Some(&entity) = entity_map.to_client().get(&event.entity) {
+            entity_events.send(EntityEvent {
+                entity,
+                event: event.event,
+            });
+        } else if **last_tick >= event.tick {
+            debug!("dropped entity event {event:?} for unknown entity");
+        } else {
+            events.push(event);
+        }
+    }
+    buffer.0 = events;
+}
+
+fn sending_system<T: Event + Serialize + Debug>(
+    mut server: ResMut<RenetServer>,
+    mut entity_events: EventReader<ToEntity<T>>,
+    channel: Res<EventChannel<ToEntity<T>>>,
+    compression: Res<NetworkCompression>,
+    rooms: Res<Rooms>,
+    network_tick: Res<NetworkTick>,
+    entities: Query<Option<&RoomId>, With<Replication>>,
+) {
+    // Events are sent every frame, so the entity may be replicated only on the next tick.
+    let mut tick = *network_tick;
+    if !network_tick.is_changed() {
+        tick.increment();
+    }
+
+    for ToEntity { entity, event } in &mut entity_events {
+        let Ok(room_id) = entities.get(*entity) else {
+            debug!("ignored entity event {event:?} for non-replicated entity {entity:?}");
+            continue;
+        };
+
+        let message = DefaultOptions::new()
+            .serialize(&EntityEventMessage {
+                tick,
+                entity: *entity,
+                event,
+            })
+            .expect("entity event should be serializable");
+        let message = channel
+            .pack(&compression, message)
+            .expect("entity event should be compressible");
+
+        for client_id in server.clients_id() {
+            if rooms.is_visible(room_id.copied(), client_id) {
+                server.send_message(client_id, channel.id, message.clone());
+            }
+        }
+        debug!("sent entity event {event:?} for {entity:?}");
+    }
+}
+
+/// Transforms [`ToEntity<T>`] events into [`EntityEvent<T>`] events to "emulate"
+/// message sending for offline mode or when server is also a player
+fn local_resending_system<T: Event + Debug>(
+    mut entity_events: ResMut<Events<ToEntity<T>>>,
+    mut local_events: EventWriter<EntityEvent<T>>,
+    rooms: Option<Res<Rooms>>,
+    entities: Query<Option<&RoomId>, With<Replication>>,
+) {
+    for ToEntity { entity, event } in entity_events.drain() {
+        let visible = entities.get(entity).is_ok_and(|room_id| {
+            rooms.as_ref().map_or(room_id.is_none(), |rooms| {
+                rooms.is_visible(room_id.copied(), SERVER_ID)
+            })
+        });
+        if visible {
+            debug!("converted entity event {event:?} for {entity:?} into a local");
+            local_events.send(EntityEvent { entity, event });
+        }
+    }
+}
+
+fn reset_system<T: Event>(mut buffer: ResMut<EntityEventBuffer<T>>) {
+    buffer.0.clear();
+}
+
+/// Received events whose entities are not known to the client yet.
+#[derive(Resource)]
+struct EntityEventBuffer<T>(Vec<EntityEventMessage<T>>);
+
+impl<T> Default for EntityEventBuffer<T> {
+    fn default() -> Self {
+        Self(Default::default())
+    }
+}
+
+/// Serialized representation of [`ToEntity`] with the server tick at which it was sent.
+#[derive(Debug, Deserialize, Serialize)]
+struct EntityEventMessage<T> {
+    tick: NetworkTick,
+    entity: Entity,
+    event: T,
+}
+
+/// An event about an entity that will be send to clients that replicate it.
+#[derive(Clone, Copy, Debug, Event)]
+pub struct ToEntity<T> {
+    pub entity: Entity,
+    pub event: T,
+}
+
+/// An event about an entity that was received from server.
+///
+/// The entity is already mapped to the client entity.
+#[derive(Clone, Copy, Debug, Event)]
+pub struct EntityEvent<T> {
+    pub entity: Entity,
+    pub event: T,
+}
@@ -0,0 +1,273 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use bevy::{ecs::event::Event, prelude::*, utils::HashMap};
+use bevy_renet::{
+    renet::{Bytes, RenetClient, RenetServer, SendType, ServerEvent},
+    transport::client_connected,
+};
+use bincode::{DefaultOptions, Options};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    server_event::{self, ToClients},
+    EventChannel,
+};
+use crate::{
+    client::ClientSet,
+    replicon_core::{compression::NetworkCompression, NetworkChannels, NetworkTick},
+    server::{has_authority, rooms::Rooms, ServerSet},
+};
+
+/// An extension trait for [`App`] for creating server events that keep only the latest value in flight.
+pub trait LatestEventAppExt {
+    /// Registers event `T` that will be emitted on client after sending [`ToClients<T>`] on server.
+    ///
+    /// Suitable for state-like events, like the current objective. Unlike other server events,
+    /// only the most recent event per key (see [`Superseding`]) is kept for each client.
+    /// It's resent on each server tick until the client acknowledges it. Older unacknowledged
+    /// events with the same key are superseded and never delivered. Clients also ignore events
+    /// that arrive after a newer event with the same key.
+    fn add_latest_server_event<T: Event + Serialize + DeserializeOwned + Debug>(
+        &mut self,
+        superseding: Superseding<T>,
+    ) -> &mut Self;
+}
+
+impl LatestEventAppExt for App {
+    fn add_latest_server_event<T: Event + Serialize + DeserializeOwned + Debug>(
+        &mut self,
+        superseding: Superseding<T>,
+    ) -> &mut Self {
+        let mut channels = self.world.resource_mut::<NetworkChannels>();
+        let channel_id = channels.create_server_channel(SendType::Unreliable);
+        let ack_channel_id = channels.create_client_channel(SendType::Unreliable);
+
+        self.add_event::<T>()
+            .init_resource::<Events<ToClients<T>>>()
+            .insert_resource(EventChannel::<T>::new(channel_id))
+            .insert_resource(AckChannel::<T>::new(ack_channel_id))
+            .insert_resource(UnackedEvents::new(superseding))
+            .init_resource::<ReceivedSequences<T>>()
+            .add_systems(
+                PreUpdate,
+                (
+                    receiving_system::<T>
+                        .in_set(ClientSet::Receive)
+                        .run_if(client_connected()),
+                    acks_receiving_system::<T>
+                        .in_set(ServerSet::Receive)
+                        .run_if(resource_exists::<RenetServer>()),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    (
+                        sending_system::<T>.run_if(resource_exists::<RenetServer>()),
+                        server_event::local_resending_system::<T>.run_if(has_authority()),
+                    )
+                        .chain()
+                        .in_set(ServerSet::SendEvents),
+                    server_reset_system::<T>.run_if(resource_removed::<RenetServer>()),
+                    client_reset_system::<T>.run_if(resource_removed::<RenetClient>()),
+                ),
+            )
+    }
+}
+
+fn receiving_system<T: Event + DeserializeOwned + Debug>(
+    mut server_events: EventWriter<T>,
+    mut client: ResMut<RenetClient>,
+    mut received_sequences: ResMut<ReceivedSequences<T>>,
+    channel: Res<EventChannel<T>>,
+    ack_channel: Res<AckChannel<T>>,
+    compression: Res<NetworkCompression>,
+) {
+    let mut acks = Vec::new();
+    while let Some(message) = client.receive_message(channel.id) {
+        let message = channel
+            .unpack(&compression, message)
+            .expect("server should send valid compressed events");
+        let (sequence, key, event): (u64, u64, T) = DefaultOptions::new()
+            .deserialize(&message)
+            .expect("server should send valid latest events");
+        acks.push(sequence);
+
+        let received_sequence = received_sequences.sequences.entry(key).or_default();
+        if *received_sequence < sequence {
+            *received_sequence = sequence;
+            debug!("received latest event {event:?} from server");
+            server_events.send(event);
+        } else {
+            debug!("ignored outdated latest event {event:?} from server");
+        }
+    }
+
+    if !acks.is_empty() {
+        let message = DefaultOptions::new()
+            .serialize(&acks)
+            .expect("event acknowledgments should be serializable");
+        client.send_message(ack_channel.id, message);
+    }
+}
+
+fn acks_receiving_system<T: Event>(
+    mut server_events: EventReader<ServerEvent>,
+    mut server: ResMut<RenetServer>,
+    mut unacked_events: ResMut<UnackedEvents<T>>,
+    ack_channel: Res<AckChannel<T>>,
+) {
+    for event in &mut server_events {
+        if let ServerEvent::ClientDisconnected { client_id, .. } = event {
+            unacked_events.clients.remove(client_id);
+        }
+    }
+
+    for client_id in server.clients_id() {
+        while let Some(message) = server.receive_message(client_id, ack_channel.id) {
+            match DefaultOptions::new().deserialize::<Vec<u64>>(&message) {
+                Ok(acks) => {
+                    if let Some(messages) = unacked_events.clients.get_mut(&client_id) {
+                        messages.retain(|_, &mut (sequence, _)| !acks.contains(&sequence));
+                    }
+                }
+                Err(e) => {
+                    error!(
+                        "unable to deserialize event acknowledgments from client {client_id}: {e}"
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// Replaces unacknowledged events with new ones and sends all of them on server tick.
+fn sending_system<T: Event + Serialize + Debug>(
+    mut server: ResMut<RenetServer>,
+    mut server_events: EventReader<ToClients<T>>,
+    mut unacked_events: ResMut<UnackedEvents<T>>,
+    channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
+    rooms: Res<Rooms>,
+    network_tick: Res<NetworkTick>,
+) {
+    for ToClients { event, mode } in &mut server_events {
+        unacked_events.sequence += 1;
+        let sequence = unacked_events.sequence;
+        let key = match unacked_events.superseding {
+            Superseding::ByType => 0,
+            Superseding::ByKey(key_fn) => (key_fn)(event),
+        };
+
+        let message = DefaultOptions::new()
+            .serialize(&(sequence, key, event))
+            .expect("server event should be serializable");
+        let message = channel
+            .pack(&compression, message)
+            .expect("server event should be compressible");
+
+        for client_id in mode.clients(&server, &rooms) {
+            unacked_events
+                .clients
+                .entry(client_id)
+                .or_default()
+                .insert(key, (sequence, message.clone()));
+        }
+        debug!("sending latest server event {event:?} to {mode:?}");
+    }
+
+    if !network_tick.is_changed() {
+        return;
+    }
+
+    for (&client_id, messages) in &unacked_events.clients {
+        if server.is_connected(client_id) {
+            for (_, message) in messages.values() {
+                server.send_message(client_id, channel.id, message.clone());
+            }
+        }
+    }
+}
+
+fn server_reset_system<T: Event>(mut unacked_events: ResMut<UnackedEvents<T>>) {
+    unacked_events.sequence = 0;
+    unacked_events.clients.clear();
+}
+
+fn client_reset_system<T: Event>(mut received_sequences: ResMut<ReceivedSequences<T>>) {
+    received_sequences.sequences.clear();
+}
+
+/// Defines which events supersede each other for [`LatestEventAppExt::add_latest_server_event`].
+pub enum Superseding<T> {
+    /// Any newer event supersedes the older one.
+    ByType,
+    /// Newer event supersedes the older one with the same key returned by the function.
+    ByKey(fn(&T) -> u64),
+}
+
+impl<T> Clone for Superseding<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Superseding<T> {}
+
+/// Holds a client channel ID for acknowledgments of latest event `T`.
+#[derive(Resource)]
+struct AckChannel<T> {
+    id: u8,
+    marker: PhantomData<T>,
+}
+
+impl<T> AckChannel<T> {
+    fn new(id: u8) -> Self {
+        Self {
+            id,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Serialized events of type `T` that weren't acknowledged by clients.
+///
+/// Used only on server.
+#[derive(Resource)]
+struct UnackedEvents<T> {
+    superseding: Superseding<T>,
+
+    /// Sequence of the last sent event.
+    sequence: u64,
+
+    /// Sequences and serialized events for each key for each client.
+    clients: HashMap<u64, HashMap<u64, (u64, Bytes)>>,
+}
+
+impl<T> UnackedEvents<T> {
+    fn new(superseding: Superseding<T>) -> Self {
+        Self {
+            superseding,
+            sequence: 0,
+            clients: Default::default(),
+        }
+    }
+}
+
+/// Sequences of the last received events for each key.
+///
+/// Used only on client.
+#[derive(Resource)]
+struct ReceivedSequences<T> {
+    sequences: HashMap<u64, u64>,
+    marker: PhantomData<T>,
+}
+
+impl<T> Default for ReceivedSequences<T> {
+    fn default() -> Self {
+        Self {
+            sequences: Default::default(),
+            marker: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use std::{any, marker::PhantomData, mem, time::Duration};
+
+use bevy::{
+    ecs::{event::Event, system::SystemParam},
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_renet::renet::{Bytes, RenetServer, ServerEvent};
+
+use super::{
+    client_event::{ClientViolation, ViolationKind},
+    EventChannel,
+};
+use crate::server::ServerSet;
+
+/// An extension trait for [`App`] for limiting how many client events each client can send.
+pub trait ClientEventLimitsAppExt {
+    /// Limits messages of already registered client event `T` for each client.
+    ///
+    /// Should be called on server.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered as client event.
+    fn limit_client_event<T: Event>(&mut self, limits: EventLimits) -> &mut Self;
+}
+
+impl ClientEventLimitsAppExt for App {
+    fn limit_client_event<T: Event>(&mut self, limits: EventLimits) -> &mut Self {
+        if !self.world.contains_resource::<EventChannel<T>>() {
+            panic!(
+                "{} should be registered as client event",
+                any::type_name::<T>()
+            );
+        }
+
+        self.insert_resource(EventLimiter::<T>::new(limits))
+            .add_systems(
+                PreUpdate,
+                disconnection_system::<T>
+                    .in_set(ServerSet::Receive)
+                    .run_if(resource_exists::<RenetServer>()),
+            )
+    }
+}
+
+fn disconnection_system<T: Event>(
+    mut server_events: EventReader<ServerEvent>,
+    mut limiter: ResMut<EventLimiter<T>>,
+) {
+    for event in &mut server_events {
+        if let ServerEvent::ClientDisconnected { client_id, .. } = event {
+            limiter.clients.remove(client_id);
+        }
+    }
+}
+
+/// Limits for messages of a client event.
+///
+/// Rate is limited using token bucket: each client has `burst` tokens that are
+/// restored with `messages_per_second` speed and each message consumes a token.
+#[derive(Clone, Copy, Debug)]
+pub struct EventLimits {
+    /// Number of messages per second that client can send on average.
+    pub messages_per_second: f32,
+
+    /// Maximum number of messages that client can send at once.
+    pub burst: u32,
+
+    /// Maximum size of a single message in bytes.
+    ///
+    /// For compressed events the size after decompression is limited too.
+    pub max_message_size: usize,
+
+    /// What to do with a client that exceeds the limits.
+    pub policy: LimitPolicy,
+}
+
+impl Default for EventLimits {
+    fn default() -> Self {
+        Self {
+            messages_per_second: 60.0,
+            burst: 60,
+            max_message_size: u16::MAX as usize,
+            policy: Default::default(),
+        }
+    }
+}
+
+/// Defines what to do with a client that exceeds [`EventLimits`].
+///
+/// [`ClientViolation`] is emitted regardless of the policy, once per client and frame for each exceeded limit.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum LimitPolicy {
+    /// Drop messages that exceed the limits.
+    #[default]
+    Drop,
+    /// Keep messages that exceed the rate and receive them later when the client gets new tokens.
+    ///
+    /// Messages that exceed the size limit are dropped.
+    Throttle,
+    /// Disconnect the client.
+    Disconnect,
+}
+
+/// Tracks limits for each client.
+#[derive(Resource)]
+struct EventLimiter<T> {
+    limits: EventLimits,
+    clients: HashMap<u64, TokenBucket>,
+    marker: PhantomData<T>,
+}
+
+impl<T> EventLimiter<T> {
+    fn new(limits: EventLimits) -> Self {
+        Self {
+            limits,
+            clients: Default::default(),
+            marker: PhantomData,
+        }
+    }
+}
+
+struct TokenBucket {
+    /// Number of messages that client can send right now.
+    tokens: f32,
+
+    /// Elapsed time when tokens were restored the last time.
+    last_refill: Duration,
+
+    /// Received message that was postponed by [`LimitPolicy::Throttle`].
+    postponed: Option<Bytes>,
+
+    /// Number of messages that exceeded the rate since the last report.
+    rate_exceeded: usize,
+
+    /// Number of messages that exceeded the size since the last report.
+    size_exceeded: usize,
+
+    /// Largest size among messages counted in [`Self::size_exceeded`].
+    largest_size: usize,
+}
+
+impl TokenBucket {
+    fn new(limits: EventLimits, elapsed: Duration) -> Self {
+        Self {
+            tokens: limits.burst as f32,
+            last_refill: elapsed,
+            postponed: None,
+            rate_exceeded: 0,
+            size_exceeded: 0,
+            largest_size: 0,
+        }
+    }
+
+    fn refill(&mut self, limits: EventLimits, elapsed: Duration) {
+        let delta = elapsed.saturating_sub(self.last_refill).as_secs_f32();
+        self.tokens = (self.tokens + delta * limits.messages_per_second).min(limits.burst as f32);
+        self.last_refill = elapsed;
+    }
+
+    /// Reports and resets counts of messages that exceeded the limits.
+    fn report<T>(&mut self, violations: &mut EventWriter<ClientViolation>, client_id: u64) {
+        if self.rate_exceeded > 0 {
+            let kind = ViolationKind::RateLimitExceeded {
+                count: mem::take(&mut self.rate_exceeded),
+            };
+            report::<T>(violations, client_id, kind);
+        }
+        if self.size_exceeded > 0 {
+            let kind = ViolationKind::SizeLimitExceeded {
+                size: mem::take(&mut self.largest_size),
+                count: mem::take(&mut self.size_exceeded),
+            };
+            report::<T>(violations, client_id, kind);
+        }
+    }
+}
+
+/// Receives messages of client event `T` with respect to [`EventLimits`] and reports violations.
+#[derive(SystemParam)]
+pub(super) struct ClientMessages<'w, T: Event> {
+    violations: EventWriter<'w, ClientViolation>,
+    limiter: Option<ResMut<'w, EventLimiter<T>>>,
+    time: Res<'w, Time>,
+}
+
+impl<T: Event> ClientMessages<'_, T> {
+    /// Returns the next message of the event from the client.
+    ///
+    /// Returns [`None`] if there are no more messages or the client exceeded the limits.
+    /// Violations are reported when [`None`] is returned, so it should be called until then for each client.
+    pub(super) fn receive(
+        &mut self,
+        server: &mut RenetServer,
+        client_id: u64,
+        channel: &EventChannel<T>,
+    ) -> Option<Bytes> {
+        let Some(limiter) = &mut self.limiter else {
+            return server.receive_message(client_id, channel.id);
+        };
+
+        let limits = limiter.limits;
+        let elapsed = self.time.elapsed();
+        let bucket = limiter
+            .clients
+            .entry(client_id)
+            .or_insert_with(|| TokenBucket::new(limits, elapsed));
+        bucket.refill(limits, elapsed);
+
+        loop {
+            let (message, postponed) = match bucket.postponed.take() {
+                Some(message) => (message, true),
+                None => match server.receive_message(client_id, channel.id) {
+                    Some(message) => (message, false),
+                    None => break,
+                },
+            };
+
+            // Check the uncompressed size too, otherwise a tiny compressed message
+            // could expand into a huge one.
+            let size = channel
+                .unpacked_len(&message)
+                .map_or(message.len(), |len| len.max(message.len()));
+            if size > limits.max_message_size {
+                bucket.size_exceeded += 1;
+                bucket.largest_size = bucket.largest_size.max(size);
+                if limits.policy == LimitPolicy::Disconnect {
+                    server.disconnect(client_id);
+                    break;
+                }
+                continue;
+            }
+
+            if bucket.tokens >= 1.0 {
+                bucket.tokens -= 1.0;
+                return Some(message);
+            }
+
+            if !postponed {
+                bucket.rate_exceeded += 1;
+            }
+            match limits.policy {
+                LimitPolicy::Drop => continue,
+                LimitPolicy::Throttle => {
+                    bucket.postponed = Some(message);
+                    break;
+                }
+                LimitPolicy::Disconnect => {
+                    server.disconnect(client_id);
+                    break;
+                }
+            }
+        }
+
+        // Report violations once the client has no more messages for this frame.
+        bucket.report::<T>(&mut self.violations, client_id);
+
+        None
+    }
+
+    /// Emits [`ClientViolation`] for the event.
+    pub(super) fn report(&mut self, client_id: u64, kind: ViolationKind) {
+        report::<T>(&mut self.violations, client_id, kind);
+    }
+}
+
+fn report<T>(violations: &mut EventWriter<ClientViolation>, client_id: u64, kind: ViolationKind) {
+    let event_name = any::type_name::<T>();
+    debug!("client {client_id} violated {event_name}: {kind:?}");
+    violations.send(ClientViolation {
+        client_id,
+        event_name,
+        kind,
+    });
+}
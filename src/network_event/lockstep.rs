@@ -0,0 +1,359 @@
+use std::{fmt::Debug, mem};
+
+use bevy::{ecs::event::Event, prelude::*, utils::HashMap};
+use bevy_renet::{
+    renet::{RenetClient, RenetServer, ServerEvent},
+    transport::client_connected,
+};
+use bincode::{DefaultOptions, Options};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    client_event::{ClientViolation, FromClient, ViolationKind},
+    limits::ClientMessages,
+    EventChannel, SendPolicy,
+};
+use crate::{
+    client::ClientSet,
+    replicon_core::{compression::NetworkCompression, NetworkChannels, NetworkTick},
+    server::{has_authority, ServerSet, SERVER_ID},
+};
+
+/// Number of steps beyond the input delay for which clients can submit commands.
+///
+/// Clients submit commands only for received steps, so they can't be further ahead.
+/// Commands for later steps are rejected to avoid buffering them indefinitely.
+const STEPS_WINDOW: u32 = 1;
+
+/// An extension trait for [`App`] for deterministic lockstep simulation.
+pub trait LockstepAppExt {
+    /// Registers command `C` for lockstep simulation.
+    ///
+    /// Send `C` on any peer and it will be emitted on all peers as part of [`LockstepStep<C>`].
+    /// Commands sent after advancing to step `N` are scheduled for step `N + input_delay`.
+    /// Server gathers commands from all clients for a step and broadcasts the combined set
+    /// on server tick, but only when every client submitted its commands for it.
+    /// Peers should advance the simulation only on [`LockstepStep<C>`] events.
+    /// Commands submitted for steps too far ahead of the server are rejected and reported as [`ClientViolation`].
+    ///
+    /// `input_delay` should be at least 1 and will be clamped otherwise.
+    fn add_lockstep_commands<C: Event + Serialize + DeserializeOwned + Debug>(
+        &mut self,
+        input_delay: u32,
+    ) -> &mut Self;
+}
+
+impl LockstepAppExt for App {
+    fn add_lockstep_commands<C: Event + Serialize + DeserializeOwned + Debug>(
+        &mut self,
+        input_delay: u32,
+    ) -> &mut Self {
+        let mut channels = self.world.resource_mut::<NetworkChannels>();
+        let commands_channel_id = channels.create_client_channel(SendPolicy::Ordered.into());
+        let steps_channel_id = channels.create_server_channel(SendPolicy::Ordered.into());
+
+        let input_delay = input_delay.max(1);
+        self.add_event::<C>()
+            .add_event::<LockstepStep<C>>()
+            .add_event::<ClientViolation>()
+            .insert_resource(EventChannel::<C>::new(commands_channel_id))
+            .insert_resource(EventChannel::<LockstepStep<C>>::new(steps_channel_id))
+            .insert_resource(ClientCommands::<C>::new(input_delay))
+            .insert_resource(ServerCommands::<C>::new(input_delay))
+            .add_systems(
+                PreUpdate,
+                (
+                    steps_receiving_system::<C>
+                        .in_set(ClientSet::Receive)
+                        .run_if(client_connected()),
+                    commands_receiving_system::<C>
+                        .in_set(ServerSet::Receive)
+                        .run_if(resource_exists::<RenetServer>()),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    commands_sending_system::<C>
+                        .in_set(ClientSet::Send)
+                        .run_if(client_connected()),
+                    stepping_system::<C>
+                        .in_set(ServerSet::SendEvents)
+                        .run_if(has_authority()),
+                    client_reset_system::<C>.run_if(resource_removed::<RenetClient>()),
+                    server_reset_system::<C>.run_if(resource_removed::<RenetServer>()),
+                ),
+            )
+    }
+}
+
+fn steps_receiving_system<C: Event + DeserializeOwned + Debug>(
+    mut steps: EventWriter<LockstepStep<C>>,
+    mut client: ResMut<RenetClient>,
+    mut client_commands: ResMut<ClientCommands<C>>,
+    channel: Res<EventChannel<LockstepStep<C>>>,
+    compression: Res<NetworkCompression>,
+) {
+    while let Some(message) = client.receive_message(channel.id) {
+        let message = channel
+            .unpack(&compression, message)
+            .expect("server should send valid compressed steps");
+        let (tick, commands): (NetworkTick, Vec<(u64, C)>) = DefaultOptions::new()
+            .deserialize(&message)
+            .expect("server should send valid lockstep steps");
+
+        client_commands.received_steps.push(tick);
+        let step = LockstepStep::new(tick, commands);
+        debug!("received lockstep step {:?}", step.tick);
+        steps.send(step);
+    }
+}
+
+/// Submits commands for each received step.
+fn commands_sending_system<C: Event + Serialize + Debug>(
+    mut commands: ResMut<Events<C>>,
+    mut client: ResMut<RenetClient>,
+    mut client_commands: ResMut<ClientCommands<C>>,
+    channel: Res<EventChannel<C>>,
+    compression: Res<NetworkCompression>,
+) {
+    client_commands.pending.extend(commands.drain());
+
+    let ClientCommands {
+        input_delay,
+        ref mut pending,
+        ref mut received_steps,
+    } = *client_commands;
+    for tick in received_steps.drain(..) {
+        let tick = NetworkTick::new(tick.get().wrapping_add(input_delay));
+        let message = DefaultOptions::new()
+            .serialize(&(tick, &*pending))
+            .expect("lockstep commands should be serializable");
+        let message = channel
+            .pack(&compression, message)
+            .expect("lockstep commands should be compressible");
+        client.send_message(channel.id, message);
+        debug!("submitted {} lockstep commands for {tick:?}", pending.len());
+        pending.clear();
+    }
+}
+
+fn commands_receiving_system<C: Event + DeserializeOwned>(
+    mut server_events: EventReader<ServerEvent>,
+    mut server: ResMut<RenetServer>,
+    mut messages: ClientMessages<C>,
+    mut server_commands: ResMut<ServerCommands<C>>,
+    channel: Res<EventChannel<C>>,
+    compression: Res<NetworkCompression>,
+) {
+    for event in &mut server_events {
+        if let ServerEvent::ClientDisconnected { client_id, .. } = event {
+            server_commands.clients.remove(client_id);
+        }
+    }
+
+    for client_id in server.clients_id() {
+        while let Some(message) = messages.receive(&mut server, client_id, &channel) {
+            let message = match channel.unpack(&compression, message) {
+                Ok(message) => message,
+                Err(e) => {
+                    error!("unable to decompress lockstep commands from client {client_id}: {e}");
+                    continue;
+                }
+            };
+            match DefaultOptions::new().deserialize::<(NetworkTick, Vec<C>)>(&message) {
+                Ok((tick, commands)) => {
+                    if tick < server_commands.next_step {
+                        warn!(
+                            "ignored lockstep commands from client {client_id} for already executed {tick:?}"
+                        );
+                        continue;
+                    }
+                    let last_allowed = NetworkTick::new(
+                        server_commands
+                            .next_step
+                            .get()
+                            .wrapping_add(server_commands.input_delay + STEPS_WINDOW),
+                    );
+                    if tick > last_allowed {
+                        messages.report(client_id, ViolationKind::StepTooFarAhead(tick));
+                        continue;
+                    }
+                    server_commands
+                        .commands
+                        .entry(tick)
+                        .or_default()
+                        .insert(client_id, commands);
+                }
+                Err(e) => {
+                    error!("unable to deserialize lockstep commands from client {client_id}: {e}")
+                }
+            }
+        }
+    }
+}
+
+/// Emits the next step on server tick if all clients submitted their commands for it.
+fn stepping_system<C: Event + Serialize + Debug>(
+    mut steps: EventWriter<LockstepStep<C>>,
+    mut commands: ResMut<Events<C>>,
+    mut server: Option<ResMut<RenetServer>>,
+    mut server_commands: ResMut<ServerCommands<C>>,
+    channel: Res<EventChannel<LockstepStep<C>>>,
+    compression: Res<NetworkCompression>,
+    network_tick: Res<NetworkTick>,
+) {
+    server_commands.pending.extend(commands.drain());
+
+    if !network_tick.is_changed() {
+        return;
+    }
+
+    let tick = server_commands.next_step;
+    let first_required = NetworkTick::new(tick.get().wrapping_add(server_commands.input_delay));
+    if let Some(server) = &server {
+        for client_id in server.clients_id_iter() {
+            server_commands
+                .clients
+                .entry(client_id)
+                .or_insert(first_required);
+        }
+    }
+
+    let step_commands = server_commands.commands.remove(&tick).unwrap_or_default();
+    let missing = server_commands
+        .clients
+        .iter()
+        .any(|(client_id, &required)| tick >= required && !step_commands.contains_key(client_id));
+    if missing {
+        debug!("waiting for lockstep commands for {tick:?}");
+        server_commands.commands.insert(tick, step_commands);
+        return;
+    }
+
+    let mut step_commands: Vec<_> = step_commands.into_iter().collect();
+    step_commands.sort_unstable_by_key(|&(client_id, _)| client_id);
+    let step_commands: Vec<_> = step_commands
+        .into_iter()
+        .flat_map(|(client_id, commands)| {
+            commands
+                .into_iter()
+                .map(move |command| (client_id, command))
+        })
+        .collect();
+
+    if let Some(server) = &mut server {
+        let message = DefaultOptions::new()
+            .serialize(&(tick, &step_commands))
+            .expect("lockstep step should be serializable");
+        let message = channel
+            .pack(&compression, message)
+            .expect("lockstep step should be compressible");
+        for &client_id in server_commands.clients.keys() {
+            server.send_message(client_id, channel.id, message.clone());
+        }
+    }
+
+    let local_commands = mem::take(&mut server_commands.pending);
+    server_commands
+        .commands
+        .entry(first_required)
+        .or_default()
+        .insert(SERVER_ID, local_commands);
+    server_commands.next_step = NetworkTick::new(tick.get().wrapping_add(1));
+
+    debug!("executed lockstep step {tick:?}");
+    steps.send(LockstepStep::new(tick, step_commands));
+}
+
+fn client_reset_system<C: Event>(mut client_commands: ResMut<ClientCommands<C>>) {
+    client_commands.pending.clear();
+    client_commands.received_steps.clear();
+}
+
+fn server_reset_system<C: Event>(mut server_commands: ResMut<ServerCommands<C>>) {
+    server_commands.next_step = Default::default();
+    server_commands.clients.clear();
+    server_commands.commands.clear();
+    server_commands.pending.clear();
+}
+
+/// A lockstep simulation step with commands from all peers.
+///
+/// Emitted on server and clients in the same order with the same commands.
+/// Commands are sorted by client ID and by sending order within a client.
+#[derive(Clone, Event)]
+pub struct LockstepStep<C> {
+    pub tick: NetworkTick,
+    pub commands: Vec<FromClient<C>>,
+}
+
+impl<C> LockstepStep<C> {
+    fn new(tick: NetworkTick, commands: Vec<(u64, C)>) -> Self {
+        Self {
+            tick,
+            commands: commands
+                .into_iter()
+                .map(|(client_id, event)| FromClient {
+                    client_id,
+                    tick: None,
+                    event,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Commands that wait for submission.
+///
+/// Used only on client.
+#[derive(Resource)]
+struct ClientCommands<C> {
+    input_delay: u32,
+    pending: Vec<C>,
+
+    /// Steps received this frame.
+    received_steps: Vec<NetworkTick>,
+}
+
+impl<C> ClientCommands<C> {
+    fn new(input_delay: u32) -> Self {
+        Self {
+            input_delay,
+            pending: Default::default(),
+            received_steps: Default::default(),
+        }
+    }
+}
+
+/// Submitted commands for upcoming steps.
+///
+/// Used only on server or in single-player.
+#[derive(Resource)]
+struct ServerCommands<C> {
+    input_delay: u32,
+
+    /// Tick of the next step to execute.
+    next_step: NetworkTick,
+
+    /// First step for which commands are required for each client.
+    clients: HashMap<u64, NetworkTick>,
+
+    /// Submitted commands for each step from each client.
+    commands: HashMap<NetworkTick, HashMap<u64, Vec<C>>>,
+
+    /// Local commands that will be submitted on the next step.
+    pending: Vec<C>,
+}
+
+impl<C> ServerCommands<C> {
+    fn new(input_delay: u32) -> Self {
+        Self {
+            input_delay,
+            next_step: Default::default(),
+            clients: Default::default(),
+            commands: Default::default(),
+            pending: Default::default(),
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use std::{any, fmt::Debug};
+
+use bevy::{
+    ecs::{event::Event, system::SystemParam},
+    prelude::*,
+};
+use bevy_renet::{
+    renet::{RenetClient, RenetServer, SendType},
+    transport::client_connected,
+};
+use bincode::{DefaultOptions, Options};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{
+    client_event::ClientViolation, init_event_mapping, limits::ClientMessages,
+    server_event::SendMode, validation::EntityValidator, EventChannel, EventMapping,
+};
+use crate::{
+    client::{ClientSet, NetworkEntityMap},
+    replicon_core::{
+        compression::NetworkCompression,
+        replication_rules::{MapNetworkEntities, Mapper},
+        NetworkChannels,
+    },
+    server::{has_authority, rooms::Rooms, ServerSet, SERVER_ID},
+};
+
+/// An extension trait for [`App`] for creating client events that are relayed by server to other clients.
+pub trait RelayedEventAppExt {
+    /// Registers [`FromPeer<T>`] event that will be emitted on recipients after sending [`ToPeers<T>`] on client.
+    ///
+    /// The server forwards the event to clients selected by [`ToPeers::mode`].
+    /// The sender never receives its own event. If the server is also a player, it receives events
+    /// targeted at [`SERVER_ID`] and can send [`ToPeers<T>`] to clients too.
+    /// Forwarded events can be filtered on server using [`Self::set_relay_filter`].
+    ///
+    /// Messages from clients are received as client event [`ToPeers<T>`], so
+    /// [`ClientEventLimitsAppExt`](super::limits::ClientEventLimitsAppExt) can be used for it.
+    fn add_relayed_event<T: Event + Serialize + DeserializeOwned + Debug>(
+        &mut self,
+        policy: impl Into<SendType>,
+    ) -> &mut Self;
+
+    /// Same as [`Self::add_relayed_event`], but additionally maps client entities to server before sending
+    /// and server entities to client after receiving.
+    ///
+    /// Events with entities that can't be mapped are handled according to [`MappingPolicy`](super::MappingPolicy)
+    /// of [`ToPeers<T>`] on sender and [`FromPeer<T>`] on recipients.
+    /// Entities can be validated on server before forwarding using
+    /// [`ClientEventValidationAppExt`](super::validation::ClientEventValidationAppExt) for [`ToPeers<T>`].
+    fn add_mapped_relayed_event<
+        T: Event + Serialize + DeserializeOwned + Debug + MapNetworkEntities,
+    >(
+        &mut self,
+        policy: impl Into<SendType>,
+    ) -> &mut Self;
+
+    /// Sets a function that decides whether already registered relayed event `T` should be forwarded.
+    ///
+    /// The function receives the sender ID, the requested mode and the event.
+    /// Rejected events are dropped.
+    ///
+    /// Should be called on server.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered as relayed event.
+    fn set_relay_filter<T: Event>(&mut self, filter: fn(u64, &SendMode, &T) -> bool) -> &mut Self;
+}
+
+impl RelayedEventAppExt for App {
+    fn add_relayed_event<T: Event + Serialize + DeserializeOwned + Debug>(
+        &mut self,
+        policy: impl Into<SendType>,
+    ) -> &mut Self {
+        add_relayed_event_with::<T, _, _, _>(
+            self,
+            policy.into(),
+            relaying_system::<T>,
+            sending_system::<T>,
+            receiving_system::<T>,
+        )
+    }
+
+    fn add_mapped_relayed_event<
+        T: Event + Serialize + DeserializeOwned + Debug + MapNetworkEntities,
+    >(
+        &mut self,
+        policy: impl Into<SendType>,
+    ) -> &mut Self {
+        add_relayed_event_with::<T, _, _, _>(
+            self,
+            policy.into(),
+            validating_and_relaying_system::<T>,
+            mapping_and_sending_system::<T>,
+            receiving_and_mapping_system::<T>,
+        );
+        init_event_mapping::<ToPeers<T>>(self);
+        init_event_mapping::<FromPeer<T>>(self);
+        self
+    }
+
+    fn set_relay_filter<T: Event>(&mut self, filter: fn(u64, &SendMode, &T) -> bool) -> &mut Self {
+        if !self.world.contains_resource::<EventChannel<ToPeers<T>>>() {
+            panic!(
+                "{} should be registered as relayed event",
+                any::type_name::<T>()
+            );
+        }
+
+        self.insert_resource(RelayFilter(filter))
+    }
+}
+
+fn add_relayed_event_with<T: Event + Serialize + Debug, Marker1, Marker2, Marker3>(
+    app: &mut App,
+    send_type: SendType,
+    relaying_system: impl IntoSystemConfigs<Marker1>,
+    sending_system: impl IntoSystemConfigs<Marker2>,
+    receiving_system: impl IntoSystemConfigs<Marker3>,
+) -> &mut App {
+    let mut channels = app.world.resource_mut::<NetworkChannels>();
+    let client_channel_id = channels.create_client_channel(send_type.clone());
+    let server_channel_id = channels.create_server_channel(send_type);
+
+    app.add_event::<ToPeers<T>>()
+        .add_event::<FromPeer<T>>()
+        .add_event::<ClientViolation>()
+        .insert_resource(EventChannel::<ToPeers<T>>::new(client_channel_id))
+        .insert_resource(EventChannel::<FromPeer<T>>::new(server_channel_id))
+        .add_systems(
+            PreUpdate,
+            (
+                relaying_system
+                    .in_set(ServerSet::Receive)
+                    .run_if(resource_exists::<RenetServer>()),
+                receiving_system
+                    .in_set(ClientSet::Receive)
+                    .run_if(client_connected()),
+            ),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                sending_system
+                    .in_set(ClientSet::Send)
+                    .run_if(client_connected()),
+                local_relaying_system::<T>
+                    .in_set(ServerSet::SendEvents)
+                    .run_if(has_authority())
+                    .run_if(resource_exists::<RenetServer>()),
+            ),
+        )
+}
+
+fn relaying_system<T: Event + Serialize + DeserializeOwned + Debug>(
+    mut server: ResMut<RenetServer>,
+    mut messages: ClientMessages<ToPeers<T>>,
+    mut relay: PeerRelay<T>,
+    channel: Res<EventChannel<ToPeers<T>>>,
+) {
+    for client_id in server.clients_id() {
+        while let Some(message) = messages.receive(&mut server, client_id, &channel) {
+            let message = match channel.unpack(&relay.compression, message) {
+                Ok(message) => message,
+                Err(e) => {
+                    error!("unable to decompress relayed event from client {client_id}: {e}");
+                    continue;
+                }
+            };
+            match DefaultOptions::new().deserialize(&message) {
+                Ok(ToPeers { mode, event }) => {
+                    debug!("received relayed event {event:?} from client {client_id}");
+                    relay.relay(&mut server, client_id, mode, event);
+                }
+                Err(e) => {
+                    error!("unable to deserialize relayed event from client {client_id}: {e}")
+                }
+            }
+        }
+    }
+}
+
+fn validating_and_relaying_system<
+    T: Event + MapNetworkEntities + Serialize + DeserializeOwned + Debug,
+>(
+    mut server: ResMut<RenetServer>,
+    mut messages: ClientMessages<ToPeers<T>>,
+    mut relay: PeerRelay<T>,
+    validator: EntityValidator,
+    channel: Res<EventChannel<ToPeers<T>>>,
+) {
+    for client_id in server.clients_id() {
+        while let Some(message) = messages.receive(&mut server, client_id, &channel) {
+            let message = match channel.unpack(&relay.compression, message) {
+                Ok(message) => message,
+                Err(e) => {
+                    error!(
+                        "unable to decompress mapped relayed event from client {client_id}: {e}"
+                    );
+                    continue;
+                }
+            };
+            match DefaultOptions::new().deserialize::<ToPeers<T>>(&message) {
+                Ok(mut event) => {
+                    debug!("received mapped relayed event {event:?} from client {client_id}");
+                    match validator.validate(client_id, &mut event, channel.validation) {
+                        Ok(()) => relay.relay(&mut server, client_id, event.mode, event.event),
+                        Err(kind) => messages.report(client_id, kind),
+                    }
+                }
+                Err(e) => {
+                    error!(
+                        "unable to deserialize mapped relayed event from client {client_id}: {e}"
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// Relays [`ToPeers<T>`] events that were sent by server when it's also a player.
+fn local_relaying_system<T: Event + Serialize + Debug>(
+    mut server: ResMut<RenetServer>,
+    mut events: ResMut<Events<ToPeers<T>>>,
+    mut relay: PeerRelay<T>,
+) {
+    for ToPeers { mode, event } in events.drain() {
+        debug!("relaying local event {event:?}");
+        relay.relay(&mut server, SERVER_ID, mode, event);
+    }
+}
+
+fn sending_system<T: Event + Serialize + Debug>(
+    mut events: EventReader<ToPeers<T>>,
+    mut client: ResMut<RenetClient>,
+    channel: Res<EventChannel<ToPeers<T>>>,
+    compression: Res<NetworkCompression>,
+) {
+    for event in &mut events {
+        let message = DefaultOptions::new()
+            .serialize(&event)
+            .expect("relayed event should be serializable");
+        let message = channel
+            .pack(&compression, message)
+            .expect("relayed event should be compressible");
+        client.send_message(channel.id, message);
+        debug!("sent relayed event {event:?}");
+    }
+}
+
+fn mapping_and_sending_system<T: Event + MapNetworkEntities + Serialize + Debug>(
+    mut events: ResMut<Events<ToPeers<T>>>,
+    mut client: ResMut<RenetClient>,
+    mut event_mapping: EventMapping<ToPeers<T>>,
+    entity_map: Res<NetworkEntityMap>,
+    channel: Res<EventChannel<ToPeers<T>>>,
+    compression: Res<NetworkCompression>,
+) {
+    for event in event_mapping.map(
+        events.drain(),
+        entity_map.to_server(),
+        channel.mapping_policy,
+    ) {
+        let message = DefaultOptions::new()
+            .serialize(&event)
+            .expect("mapped relayed event should be serializable");
+        let message = channel
+            .pack(&compression, message)
+            .expect("mapped relayed event should be compressible");
+        client.send_message(channel.id, message);
+        debug!("sent mapped relayed event {event:?}");
+    }
+}
+
+fn receiving_system<T: Event + DeserializeOwned + Debug>(
+    mut peer_events: EventWriter<FromPeer<T>>,
+    mut client: ResMut<RenetClient>,
+    channel: Res<EventChannel<FromPeer<T>>>,
+    compression: Res<NetworkCompression>,
+) {
+    while let Some(message) = client.receive_message(channel.id) {
+        let message = channel
+            .unpack(&compression, message)
+            .expect("server should send valid compressed relayed events");
+        let (sender, event) = DefaultOptions::new()
+            .deserialize(&message)
+            .expect("server should send valid relayed events");
+        debug!("received relayed event {event:?} from client {sender}");
+        peer_events.send(FromPeer { sender, event });
+    }
+}
+
+fn receiving_and_mapping_system<T: Event + MapNetworkEntities + DeserializeOwned + Debug>(
+    mut peer_events: EventWriter<FromPeer<T>>,
+    mut client: ResMut<RenetClient>,
+    mut event_mapping: EventMapping<FromPeer<T>>,
+    entity_map: Res<NetworkEntityMap>,
+    channel: Res<EventChannel<FromPeer<T>>>,
+    compression: Res<NetworkCompression>,
+) {
+    let mut events = Vec::new();
+    while let Some(message) = client.receive_message(channel.id) {
+        let message = channel
+            .unpack(&compression, message)
+            .expect("server should send valid compressed relayed events");
+        let (sender, event) = DefaultOptions::new()
+            .deserialize(&message)
+            .expect("server should send valid mapped relayed events");
+        debug!("received mapped relayed event {event:?} from client {sender}");
+        events.push(FromPeer { sender, event });
+    }
+    peer_events.send_batch(event_mapping.map(
+        events,
+        entity_map.to_client(),
+        channel.mapping_policy,
+    ));
+}
+
+/// Forwards relayed events of type `T` to their recipients.
+#[derive(SystemParam)]
+struct PeerRelay<'w, T: Event> {
+    local_events: EventWriter<'w, FromPeer<T>>,
+    channel: Res<'w, EventChannel<FromPeer<T>>>,
+    compression: Res<'w, NetworkCompression>,
+    rooms: Res<'w, Rooms>,
+    filter: Option<Res<'w, RelayFilter<T>>>,
+}
+
+impl<T: Event + Serialize + Debug> PeerRelay<'_, T> {
+    /// Sends the event from `sender` to all clients selected by `mode` except the sender.
+    ///
+    /// Emits [`FromPeer<T>`] locally if the server is one of recipients.
+    fn relay(&mut self, server: &mut RenetServer, sender: u64, mode: SendMode, event: T) {
+        if let Some(filter) = &self.filter {
+            if !(filter.0)(sender, &mode, &event) {
+                debug!("filtered relayed event {event:?} from client {sender}");
+                return;
+            }
+        }
+
+        let message = DefaultOptions::new()
+            .serialize(&(sender, &event))
+            .expect("relayed event should be serializable");
+        let message = self
+            .channel
+            .pack(&self.compression, message)
+            .expect("relayed event should be compressible");
+        for client_id in mode.clients(server, &self.rooms) {
+            if client_id != sender {
+                server.send_message(client_id, self.channel.id, message.clone());
+            }
+        }
+        debug!("relayed event {event:?} from client {sender} to {mode:?}");
+
+        if sender != SERVER_ID && mode.includes_server(&self.rooms) {
+            self.local_events.send(FromPeer { sender, event });
+        }
+    }
+}
+
+/// Function that decides whether a relayed event should be forwarded.
+#[derive(Resource)]
+struct RelayFilter<T>(fn(u64, &SendMode, &T) -> bool);
+
+/// An event that will be relayed by server to other clients.
+#[derive(Clone, Debug, Deserialize, Event, Serialize)]
+pub struct ToPeers<T> {
+    pub mode: SendMode,
+    pub event: T,
+}
+
+impl<T: MapNetworkEntities> MapNetworkEntities for ToPeers<T> {
+    fn map_entities<M: Mapper>(&mut self, mapper: &mut M) {
+        self.event.map_entities(mapper);
+    }
+}
+
+/// An event that was sent by another client and relayed by server.
+///
+/// For events sent by the server, `sender` is [`SERVER_ID`].
+#[derive(Clone, Copy, Debug, Event)]
+pub struct FromPeer<T> {
+    pub sender: u64,
+    pub event: T,
+}
+
+impl<T: MapNetworkEntities> MapNetworkEntities for FromPeer<T> {
+    fn map_entities<M: Mapper>(&mut self, mapper: &mut M) {
+        self.event.map_entities(mapper);
+    }
+}
@@ -0,0 +1,222 @@
+use std::{fmt::Debug, marker::PhantomData, time::Duration};
+
+use bevy::{
+    ecs::{event::Event, system::SystemParam},
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_renet::renet::SendType;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use super::{
+    client_event::{ClientEventAppExt, FromClient},
+    server_event::{SendMode, ServerEventAppExt, ToClients},
+};
+use crate::client::ClientSet;
+
+/// An extension trait for [`App`] for creating client requests that expect a response from server.
+pub trait ClientRequestAppExt {
+    /// Registers request `Req` that client sends using [`RequestSender<Req>`] and server receives as
+    /// [`FromClient<Request<Req>>`].
+    ///
+    /// Server answers with [`FromClient::respond`] and the client receives [`RequestResult<Resp>`].
+    /// If the server doesn't answer within `timeout`, the client receives [`RequestError::Timeout`].
+    ///
+    /// Internally registers [`Request<Req>`] as client event and [`Response<Resp>`] as server event,
+    /// so each `Req` and `Resp` should be registered only once.
+    fn add_client_request<Req, Resp>(
+        &mut self,
+        policy: impl Into<SendType>,
+        timeout: Duration,
+    ) -> &mut Self
+    where
+        Req: Serialize + DeserializeOwned + Debug + Send + Sync + 'static,
+        Resp: Serialize + DeserializeOwned + Debug + Send + Sync + 'static;
+}
+
+impl ClientRequestAppExt for App {
+    fn add_client_request<Req, Resp>(
+        &mut self,
+        policy: impl Into<SendType>,
+        timeout: Duration,
+    ) -> &mut Self
+    where
+        Req: Serialize + DeserializeOwned + Debug + Send + Sync + 'static,
+        Resp: Serialize + DeserializeOwned + Debug + Send + Sync + 'static,
+    {
+        let send_type = policy.into();
+        self.add_client_event::<Request<Req>>(send_type.clone())
+            .add_server_event::<Response<Resp>>(send_type)
+            .add_event::<RequestResult<Resp>>()
+            .insert_resource(PendingRequests::<Req>::new(timeout))
+            .add_systems(
+                PreUpdate,
+                (response_system::<Req, Resp>, timeout_system::<Req, Resp>)
+                    .chain()
+                    .after(ClientSet::Receive),
+            )
+    }
+}
+
+/// Matches received responses with pending requests.
+///
+/// Responses for unknown requests (for example, arrived after timeout) are ignored.
+fn response_system<Req: Send + Sync + 'static, Resp: Debug + Send + Sync + 'static>(
+    mut responses: ResMut<Events<Response<Resp>>>,
+    mut results: EventWriter<RequestResult<Resp>>,
+    mut pending_requests: ResMut<PendingRequests<Req>>,
+) {
+    for Response { id, response } in responses.drain() {
+        if pending_requests.sent.remove(&id).is_some() {
+            results.send(RequestResult {
+                id,
+                result: Ok(response),
+            });
+        } else {
+            debug!("ignored response {response:?} for unknown request {id:?}");
+        }
+    }
+}
+
+/// Fails requests that weren't answered in time.
+fn timeout_system<Req: Send + Sync + 'static, Resp: Send + Sync + 'static>(
+    time: Res<Time>,
+    mut results: EventWriter<RequestResult<Resp>>,
+    mut pending_requests: ResMut<PendingRequests<Req>>,
+) {
+    let PendingRequests {
+        timeout,
+        ref mut sent,
+        ..
+    } = *pending_requests;
+
+    sent.retain(|&id, &mut sent_at| {
+        if time.elapsed().saturating_sub(sent_at) < timeout {
+            true
+        } else {
+            results.send(RequestResult {
+                id,
+                result: Err(RequestError::Timeout),
+            });
+            false
+        }
+    });
+}
+
+/// Sends requests of type `T` from client and tracks them until a response or timeout.
+#[derive(SystemParam)]
+pub struct RequestSender<'w, T: Send + Sync + 'static> {
+    requests: EventWriter<'w, Request<T>>,
+    pending_requests: ResMut<'w, PendingRequests<T>>,
+    time: Res<'w, Time>,
+}
+
+impl<T: Send + Sync + 'static> RequestSender<'_, T> {
+    /// Sends request to server and returns its ID.
+    ///
+    /// The ID will be present in the corresponding [`RequestResult`].
+    pub fn send(&mut self, request: T) -> RequestId {
+        let id = self.pending_requests.next_id();
+        self.pending_requests.sent.insert(id, self.time.elapsed());
+        self.requests.send(Request { id, request });
+        id
+    }
+}
+
+/// Requests of type `T` that are waiting for response.
+#[derive(Resource)]
+pub struct PendingRequests<T> {
+    /// Time after which the request is considered failed.
+    timeout: Duration,
+
+    /// Requests and elapsed time when they were sent.
+    sent: HashMap<RequestId, Duration>,
+
+    /// ID that will be assigned to the next request.
+    next_id: RequestId,
+
+    marker: PhantomData<T>,
+}
+
+impl<T> PendingRequests<T> {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            sent: Default::default(),
+            next_id: RequestId(0),
+            marker: PhantomData,
+        }
+    }
+
+    fn next_id(&mut self) -> RequestId {
+        let id = self.next_id;
+        self.next_id.0 = self.next_id.0.wrapping_add(1);
+        id
+    }
+
+    /// Returns `true` if the request is still waiting for response.
+    pub fn contains(&self, id: RequestId) -> bool {
+        self.sent.contains_key(&id)
+    }
+
+    /// Returns the number of requests waiting for response.
+    pub fn len(&self) -> usize {
+        self.sent.len()
+    }
+
+    /// Returns `true` if there is no requests waiting for response.
+    pub fn is_empty(&self) -> bool {
+        self.sent.is_empty()
+    }
+}
+
+/// Request ID that unique for each request type on a client.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct RequestId(u32);
+
+/// A request from client.
+///
+/// Received on server as [`FromClient<Request<T>>`].
+#[derive(Clone, Debug, Deserialize, Event, Serialize)]
+pub struct Request<T> {
+    pub id: RequestId,
+    pub request: T,
+}
+
+impl<T> FromClient<Request<T>> {
+    /// Creates a response that will be sent only to the client that made the request.
+    pub fn respond<R>(&self, response: R) -> ToClients<Response<R>> {
+        ToClients {
+            mode: SendMode::Direct(self.client_id),
+            event: Response {
+                id: self.event.id,
+                response,
+            },
+        }
+    }
+}
+
+/// A response from server for [`Request`] with the same ID.
+///
+/// Should be created using [`FromClient::respond`].
+#[derive(Clone, Debug, Deserialize, Event, Serialize)]
+pub struct Response<T> {
+    pub id: RequestId,
+    pub response: T,
+}
+
+/// An event indicating that a request was answered or failed.
+///
+/// Emitted only on client (or on server in single-player).
+#[derive(Debug, Event)]
+pub struct RequestResult<T> {
+    pub id: RequestId,
+    pub result: Result<T, RequestError>,
+}
+
+/// Reason why a request failed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RequestError {
+    /// Server didn't answer in time.
+    Timeout,
+}
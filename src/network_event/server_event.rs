@@ -1,22 +1,36 @@
 use std::fmt::Debug;
 
-use bevy::{ecs::event::Event, prelude::*};
+use bevy::{
+    ecs::{event::Event, system::SystemParam},
+    prelude::*,
+    reflect::GetTypeRegistration,
+    utils::HashMap,
+};
 use bevy_renet::{
-    renet::{RenetClient, RenetServer, SendType},
+    renet::{Bytes, RenetClient, RenetServer, SendType},
     transport::client_connected,
 };
 use bincode::{DefaultOptions, Options};
 use serde::{
     de::{DeserializeOwned, DeserializeSeed},
-    Serialize,
+    Deserialize, Serialize,
 };
 
-use super::{BuildEventDeserializer, BuildEventSerializer, EventChannel};
+use super::{
+    init_event_mapping, write_batched, BuildEventDeserializer, BuildEventSerializer, EventChannel,
+    EventMapping, ReflectEventSerde, SendTiming,
+};
 use crate::{
     client::{ClientSet, NetworkEntityMap},
-    network_event::EventMapper,
-    replicon_core::{replication_rules::MapNetworkEntities, NetworkChannels},
-    server::{has_authority, ServerSet, SERVER_ID},
+    replicon_core::{
+        compression::NetworkCompression, replication_rules::MapNetworkEntities, NetworkChannels,
+        NetworkTick,
+    },
+    server::{
+        has_authority,
+        rooms::{RoomId, Rooms},
+        ServerSet, SERVER_ID,
+    },
 };
 
 /// An extension trait for [`App`] for creating server events.
@@ -28,6 +42,8 @@ pub trait ServerEventAppExt {
     ) -> &mut Self;
 
     /// Same as [`Self::add_server_event`], but additionally maps server entities to client after receiving.
+    ///
+    /// Events with entities that can't be mapped are handled according to [`MappingPolicy`](super::MappingPolicy).
     fn add_mapped_server_event<
         T: Event + Serialize + DeserializeOwned + Debug + MapNetworkEntities,
     >(
@@ -47,7 +63,14 @@ pub trait ServerEventAppExt {
         for<'a> S::EventSerializer<'a>: Serialize,
         for<'a, 'de> D::EventDeserializer<'a>: DeserializeSeed<'de, Value = T>;
 
+    /// Same as [`Self::add_server_reflect_event`], but uses [`ReflectEventSerde`] and registers `T` in [`AppTypeRegistry`].
+    fn add_server_event_reflect<T>(&mut self, policy: impl Into<SendType>) -> &mut Self
+    where
+        T: Event + Debug + FromReflect + GetTypeRegistration;
+
     /// Same as [`Self::add_server_reflect_event`], but additionally maps client entities to client after receiving.
+    ///
+    /// Events with entities that can't be mapped are handled according to [`MappingPolicy`](super::MappingPolicy).
     fn add_mapped_server_reflect_event<T, S, D>(
         &mut self,
         policy: impl Into<SendType>,
@@ -86,7 +109,9 @@ impl ServerEventAppExt for App {
             policy,
             sending_system::<T>,
             receiving_and_mapping_system::<T>,
-        )
+        );
+        init_event_mapping::<T>(self);
+        self
     }
 
     fn add_server_reflect_event<T, S, D>(&mut self, policy: impl Into<SendType>) -> &mut Self
@@ -104,6 +129,14 @@ impl ServerEventAppExt for App {
         )
     }
 
+    fn add_server_event_reflect<T>(&mut self, policy: impl Into<SendType>) -> &mut Self
+    where
+        T: Event + Debug + FromReflect + GetTypeRegistration,
+    {
+        self.register_type::<T>()
+            .add_server_reflect_event::<T, ReflectEventSerde<T>, ReflectEventSerde<T>>(policy)
+    }
+
     fn add_mapped_server_reflect_event<T, S, D>(&mut self, policy: impl Into<SendType>) -> &mut Self
     where
         T: Event + Debug + MapNetworkEntities,
@@ -116,7 +149,9 @@ impl ServerEventAppExt for App {
             policy,
             sending_reflect_system::<T, S>,
             receiving_and_mapping_reflect_system::<T, D>,
-        )
+        );
+        init_event_mapping::<T>(self);
+        self
     }
 
     fn add_server_event_with<T: Event + Debug, Marker1, Marker2>(
@@ -146,7 +181,7 @@ impl ServerEventAppExt for App {
                     local_resending_system::<T>.run_if(has_authority()),
                 )
                     .chain()
-                    .in_set(ServerSet::Send),
+                    .in_set(ServerSet::SendEvents),
             );
 
         self
@@ -157,36 +192,61 @@ fn receiving_system<T: Event + DeserializeOwned + Debug>(
     mut server_events: EventWriter<T>,
     mut client: ResMut<RenetClient>,
     channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
 ) {
     while let Some(message) = client.receive_message(channel.id) {
-        let event = DefaultOptions::new()
-            .deserialize(&message)
-            .expect("server should send valid events");
-        debug!("received event {event:?} from server");
-        server_events.send(event);
+        let message = channel
+            .unpack(&compression, message)
+            .expect("server should send valid compressed events");
+        for message in channel
+            .split(message)
+            .expect("server should send valid event batches")
+        {
+            let event = DefaultOptions::new()
+                .deserialize(&message)
+                .expect("server should send valid events");
+            debug!("received event {event:?} from server");
+            server_events.send(event);
+        }
     }
 }
 
 fn receiving_and_mapping_system<T: Event + MapNetworkEntities + DeserializeOwned + Debug>(
     mut server_events: EventWriter<T>,
     mut client: ResMut<RenetClient>,
+    mut event_mapping: EventMapping<T>,
     entity_map: Res<NetworkEntityMap>,
     channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
 ) {
+    let mut events = Vec::new();
     while let Some(message) = client.receive_message(channel.id) {
-        let mut event: T = DefaultOptions::new()
-            .deserialize(&message)
-            .expect("server should send valid mapped events");
-        debug!("received mapped event {event:?} from server");
-        event.map_entities(&mut EventMapper(entity_map.to_client()));
-        server_events.send(event);
+        let message = channel
+            .unpack(&compression, message)
+            .expect("server should send valid compressed events");
+        for message in channel
+            .split(message)
+            .expect("server should send valid event batches")
+        {
+            let event: T = DefaultOptions::new()
+                .deserialize(&message)
+                .expect("server should send valid mapped events");
+            debug!("received mapped event {event:?} from server");
+            events.push(event);
+        }
     }
+    server_events.send_batch(event_mapping.map(
+        events,
+        entity_map.to_client(),
+        channel.mapping_policy,
+    ));
 }
 
 fn receiving_reflect_system<T, D>(
     mut server_events: EventWriter<T>,
     mut client: ResMut<RenetClient>,
     channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
     registry: Res<AppTypeRegistry>,
 ) where
     T: Event + Debug,
@@ -195,20 +255,31 @@ fn receiving_reflect_system<T, D>(
 {
     let registry = registry.read();
     while let Some(message) = client.receive_message(channel.id) {
-        let mut deserializer = bincode::Deserializer::from_slice(&message, DefaultOptions::new());
-        let event = D::new(&registry)
-            .deserialize(&mut deserializer)
-            .expect("server should send valid reflect events");
-        debug!("received reflect event {event:?} from server");
-        server_events.send(event);
+        let message = channel
+            .unpack(&compression, message)
+            .expect("server should send valid compressed events");
+        for message in channel
+            .split(message)
+            .expect("server should send valid event batches")
+        {
+            let mut deserializer =
+                bincode::Deserializer::from_slice(&message, DefaultOptions::new());
+            let event = D::new(&registry)
+                .deserialize(&mut deserializer)
+                .expect("server should send valid reflect events");
+            debug!("received reflect event {event:?} from server");
+            server_events.send(event);
+        }
     }
 }
 
 fn receiving_and_mapping_reflect_system<T, D>(
     mut server_events: EventWriter<T>,
     mut client: ResMut<RenetClient>,
+    mut event_mapping: EventMapping<T>,
     entity_map: Res<NetworkEntityMap>,
     channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
     registry: Res<AppTypeRegistry>,
 ) where
     T: Event + MapNetworkEntities + Debug,
@@ -216,54 +287,53 @@ fn receiving_and_mapping_reflect_system<T, D>(
     for<'a, 'de> D::EventDeserializer<'a>: DeserializeSeed<'de, Value = T>,
 {
     let registry = registry.read();
+    let mut events = Vec::new();
     while let Some(message) = client.receive_message(channel.id) {
-        let mut deserializer = bincode::Deserializer::from_slice(&message, DefaultOptions::new());
-        let mut event = D::new(&registry)
-            .deserialize(&mut deserializer)
-            .expect("server should send valid mapped reflect events");
-        debug!("received mapped reflect event {event:?} from server");
-        event.map_entities(&mut EventMapper(entity_map.to_client()));
-        server_events.send(event);
+        let message = channel
+            .unpack(&compression, message)
+            .expect("server should send valid compressed events");
+        for message in channel
+            .split(message)
+            .expect("server should send valid event batches")
+        {
+            let mut deserializer =
+                bincode::Deserializer::from_slice(&message, DefaultOptions::new());
+            let event = D::new(&registry)
+                .deserialize(&mut deserializer)
+                .expect("server should send valid mapped reflect events");
+            debug!("received mapped reflect event {event:?} from server");
+            events.push(event);
+        }
     }
+    server_events.send_batch(event_mapping.map(
+        events,
+        entity_map.to_client(),
+        channel.mapping_policy,
+    ));
 }
 
 fn sending_system<T: Event + Serialize + Debug>(
-    mut server: ResMut<RenetServer>,
     mut server_events: EventReader<ToClients<T>>,
-    channel: Res<EventChannel<T>>,
+    mut sender: ServerEventSender<T>,
 ) {
     for ToClients { event, mode } in &mut server_events {
         let message = DefaultOptions::new()
             .serialize(&event)
             .expect("server event should be serializable");
-
-        match *mode {
-            SendMode::Broadcast => {
-                server.broadcast_message(channel.id, message);
-                debug!("broadcasted server event {event:?}");
-            }
-            SendMode::BroadcastExcept(client_id) => {
-                if client_id == SERVER_ID {
-                    server.broadcast_message(channel.id, message);
-                } else {
-                    server.broadcast_message_except(client_id, channel.id, message);
-                }
-                debug!("broadcasted server event {event:?} except client {client_id}");
-            }
-            SendMode::Direct(client_id) => {
-                if client_id != SERVER_ID {
-                    server.send_message(client_id, channel.id, message);
-                    debug!("sent direct server event {event:?} to client {client_id}");
-                }
-            }
-        }
+        sender
+            .send(mode, message)
+            .expect("server event should be compressible");
+        debug!("sending server event {event:?} to {mode:?}");
     }
+
+    sender
+        .flush()
+        .expect("server event batch should be compressible");
 }
 
 fn sending_reflect_system<T, S>(
-    mut server: ResMut<RenetServer>,
     mut server_events: EventReader<ToClients<T>>,
-    channel: Res<EventChannel<T>>,
+    mut sender: ServerEventSender<T>,
     registry: Res<AppTypeRegistry>,
 ) where
     T: Event + Debug,
@@ -276,69 +346,163 @@ fn sending_reflect_system<T, S>(
         let message = DefaultOptions::new()
             .serialize(&serializer)
             .expect("server event should be serializable");
+        sender
+            .send(mode, message)
+            .expect("server event should be compressible");
+        debug!("sending server reflect event {event:?} to {mode:?}");
+    }
+
+    sender
+        .flush()
+        .expect("server event batch should be compressible");
+}
+
+/// Queues serialized events of type `T` for their clients and sends them according to [`SendTiming`].
+#[derive(SystemParam)]
+struct ServerEventSender<'w, 's, T: Event> {
+    server: ResMut<'w, RenetServer>,
+    channel: Res<'w, EventChannel<T>>,
+    compression: Res<'w, NetworkCompression>,
+    rooms: Res<'w, Rooms>,
+    network_tick: Res<'w, NetworkTick>,
+    queue: Local<'s, EventQueue>,
+}
 
-        match *mode {
-            SendMode::Broadcast => {
-                server.broadcast_message(channel.id, message);
-                debug!("broadcasted server reflect event {event:?}");
+impl<T: Event> ServerEventSender<'_, '_, T> {
+    /// Queues serialized event for all clients that should receive it with `mode`.
+    ///
+    /// Recipients are resolved immediately, so clients that connect later won't receive the event.
+    fn send(&mut self, mode: &SendMode, message: Vec<u8>) -> Result<(), bincode::Error> {
+        let client_ids = mode.clients(&self.server, &self.rooms);
+        if self.channel.batched {
+            for client_id in client_ids {
+                write_batched(self.queue.batches.entry(client_id).or_default(), &message)?;
             }
-            SendMode::BroadcastExcept(client_id) => {
-                if client_id == SERVER_ID {
-                    server.broadcast_message(channel.id, message);
-                } else {
-                    server.broadcast_message_except(client_id, channel.id, message);
-                }
-                debug!("broadcasted server reflect event {event:?} except client {client_id}");
+        } else {
+            let message = self.channel.pack(&self.compression, message)?;
+            for client_id in client_ids {
+                self.queue
+                    .messages
+                    .entry(client_id)
+                    .or_default()
+                    .push(message.clone());
             }
-            SendMode::Direct(client_id) => {
-                if client_id != SERVER_ID {
-                    server.send_message(client_id, channel.id, message);
-                    debug!("sent direct server reflect event {event:?} to client {client_id}");
+        }
+
+        Ok(())
+    }
+
+    /// Sends queued messages if it's time according to [`SendTiming`].
+    fn flush(&mut self) -> Result<(), bincode::Error> {
+        if self.channel.timing == SendTiming::NextTick && !self.network_tick.is_changed() {
+            return Ok(());
+        }
+
+        for (client_id, messages) in self.queue.messages.drain() {
+            if self.server.is_connected(client_id) {
+                for message in messages {
+                    self.server
+                        .send_message(client_id, self.channel.id, message);
                 }
             }
         }
+        for (client_id, batch) in self.queue.batches.drain() {
+            if self.server.is_connected(client_id) {
+                let message = self.channel.pack(&self.compression, batch)?;
+                self.server
+                    .send_message(client_id, self.channel.id, message);
+            }
+        }
+
+        Ok(())
     }
 }
 
+/// Serialized server events that wait for sending.
+#[derive(Default)]
+struct EventQueue {
+    /// Packed messages for each client.
+    messages: HashMap<u64, Vec<Bytes>>,
+
+    /// Length-prefixed events for each client if batching is enabled.
+    batches: HashMap<u64, Vec<u8>>,
+}
+
 /// Transforms [`ToClients<T>`] events into `T` events to "emulate"
 /// message sending for offline mode or when server is also a player
-fn local_resending_system<T: Event + Debug>(
+pub(super) fn local_resending_system<T: Event + Debug>(
     mut server_events: ResMut<Events<ToClients<T>>>,
     mut local_events: EventWriter<T>,
+    rooms: Option<Res<Rooms>>,
 ) {
+    let default_rooms = Rooms::default();
+    let rooms = rooms.as_deref().unwrap_or(&default_rooms);
     for ToClients { event, mode } in server_events.drain() {
-        match mode {
-            SendMode::Broadcast => {
-                debug!("converted broadcasted server event {event:?} into a local");
-                local_events.send(event);
-            }
-            SendMode::BroadcastExcept(client_id) => {
-                if client_id != SERVER_ID {
-                    debug!("converted broadcasted server event {event:?} except client {client_id} into a local");
-                    local_events.send(event);
-                }
-            }
-            SendMode::Direct(client_id) => {
-                if client_id == SERVER_ID {
-                    debug!("converted direct server event {event:?} into a local");
-                    local_events.send(event);
-                }
-            }
+        if mode.includes_server(rooms) {
+            debug!("converted server event {event:?} with mode {mode:?} into a local");
+            local_events.send(event);
         }
     }
 }
 
 /// An event that will be send to client(s).
-#[derive(Clone, Copy, Debug, Event)]
+#[derive(Clone, Debug, Event)]
 pub struct ToClients<T> {
     pub mode: SendMode,
     pub event: T,
 }
 
 /// Type of server message sending.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum SendMode {
     Broadcast,
     BroadcastExcept(u64),
     Direct(u64),
+    /// Send to all clients in the room, see [`Rooms`].
+    Room(RoomId),
+    /// Send to the listed clients.
+    Clients(Vec<u64>),
+}
+
+impl SendMode {
+    /// Returns connected clients that should receive an event with this mode.
+    ///
+    /// [`SERVER_ID`] is never included.
+    pub(super) fn clients(&self, server: &RenetServer, rooms: &Rooms) -> Vec<u64> {
+        match *self {
+            SendMode::Broadcast => server.clients_id(),
+            SendMode::BroadcastExcept(client_id) => server
+                .clients_id()
+                .into_iter()
+                .filter(|&id| id != client_id)
+                .collect(),
+            SendMode::Direct(client_id) => {
+                if server.is_connected(client_id) {
+                    vec![client_id]
+                } else {
+                    Vec::new()
+                }
+            }
+            SendMode::Room(room_id) => rooms
+                .room_clients(room_id)
+                .filter(|&client_id| server.is_connected(client_id))
+                .collect(),
+            SendMode::Clients(ref client_ids) => client_ids
+                .iter()
+                .copied()
+                .filter(|&client_id| server.is_connected(client_id))
+                .collect(),
+        }
+    }
+
+    /// Returns `true` if [`SERVER_ID`] should receive an event with this mode.
+    pub(super) fn includes_server(&self, rooms: &Rooms) -> bool {
+        match *self {
+            SendMode::Broadcast => true,
+            SendMode::BroadcastExcept(client_id) => client_id != SERVER_ID,
+            SendMode::Direct(client_id) => client_id == SERVER_ID,
+            SendMode::Room(room_id) => rooms.contains(room_id, SERVER_ID),
+            SendMode::Clients(ref client_ids) => client_ids.contains(&SERVER_ID),
+        }
+    }
 }
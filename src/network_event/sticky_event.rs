@@ -0,0 +1,170 @@
+use std::{collections::VecDeque, fmt::Debug};
+
+use bevy::{ecs::event::Event, prelude::*};
+use bevy_renet::renet::{Bytes, RenetServer, SendType, ServerEvent};
+use bincode::{DefaultOptions, Options};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{
+    server_event::{SendMode, ServerEventAppExt, ToClients},
+    EventChannel,
+};
+use crate::{
+    replicon_core::compression::NetworkCompression,
+    server::{has_authority, ServerSet},
+};
+
+/// An extension trait for [`App`] for creating server events that are replayed to late-joining clients.
+pub trait StickyEventAppExt {
+    /// Same as [`ServerEventAppExt::add_server_event`], but broadcasted events are retained
+    /// according to `retention` and automatically sent to each newly connected client
+    /// before other events of this type.
+    ///
+    /// Only events with [`SendMode::Broadcast`] are retained.
+    /// Retained events can be accessed in [`StickyEvents<T>`] resource.
+    fn add_sticky_server_event<T: Event + Serialize + DeserializeOwned + Debug>(
+        &mut self,
+        policy: impl Into<SendType>,
+        retention: Retention<T>,
+    ) -> &mut Self;
+}
+
+impl StickyEventAppExt for App {
+    fn add_sticky_server_event<T: Event + Serialize + DeserializeOwned + Debug>(
+        &mut self,
+        policy: impl Into<SendType>,
+        retention: Retention<T>,
+    ) -> &mut Self {
+        self.add_server_event::<T>(policy)
+            .insert_resource(StickyEvents::new(retention))
+            .add_systems(
+                PostUpdate,
+                (
+                    connection_system::<T>.run_if(resource_exists::<RenetServer>()),
+                    retention_system::<T>.run_if(has_authority()),
+                )
+                    .chain()
+                    .before(ServerSet::Send),
+            )
+    }
+}
+
+/// Sends retained events to newly connected clients.
+fn connection_system<T: Event>(
+    mut server_events: EventReader<ServerEvent>,
+    mut server: ResMut<RenetServer>,
+    channel: Res<EventChannel<T>>,
+    sticky_events: Res<StickyEvents<T>>,
+) {
+    for event in &mut server_events {
+        if let ServerEvent::ClientConnected { client_id } = *event {
+            for (_, message) in &sticky_events.messages {
+                server.send_message(client_id, channel.id, message.clone());
+            }
+            debug!(
+                "sent {} sticky events to client {client_id}",
+                sticky_events.len()
+            );
+        }
+    }
+}
+
+/// Serializes and retains broadcasted events.
+fn retention_system<T: Event + Serialize + Debug>(
+    mut server_events: EventReader<ToClients<T>>,
+    mut sticky_events: ResMut<StickyEvents<T>>,
+    channel: Res<EventChannel<T>>,
+    compression: Res<NetworkCompression>,
+) {
+    for ToClients { event, mode } in &mut server_events {
+        if !matches!(mode, SendMode::Broadcast) {
+            continue;
+        }
+
+        let message = DefaultOptions::new()
+            .serialize(&event)
+            .expect("sticky event should be serializable");
+        let message = channel
+            .frame(message)
+            .expect("sticky event should be batchable");
+        let message = channel
+            .pack(&compression, message)
+            .expect("sticky event should be compressible");
+        sticky_events.retain(event, message);
+        debug!("retained sticky event {event:?}");
+    }
+}
+
+/// Defines which broadcasted sticky events should be kept for late-joining clients.
+pub enum Retention<T> {
+    /// Keep the specified number of last events.
+    Last(usize),
+    /// Keep the last event for each key returned by the function.
+    LastPerKey(fn(&T) -> u64),
+    /// Keep all events until [`StickyEvents::clear`] is called.
+    UntilCleared,
+}
+
+impl<T> Clone for Retention<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Retention<T> {}
+
+/// Retained serialized events of type `T` that will be sent to newly connected clients.
+///
+/// Used only on server.
+#[derive(Resource)]
+pub struct StickyEvents<T> {
+    retention: Retention<T>,
+
+    /// Serialized events in sending order with their keys for [`Retention::LastPerKey`].
+    messages: VecDeque<(Option<u64>, Bytes)>,
+}
+
+impl<T> StickyEvents<T> {
+    fn new(retention: Retention<T>) -> Self {
+        Self {
+            retention,
+            messages: Default::default(),
+        }
+    }
+
+    fn retain(&mut self, event: &T, message: Bytes) {
+        match self.retention {
+            Retention::Last(count) => {
+                if count == 0 {
+                    return;
+                }
+                if self.messages.len() == count {
+                    self.messages.pop_front();
+                }
+                self.messages.push_back((None, message));
+            }
+            Retention::LastPerKey(key_fn) => {
+                let key = Some((key_fn)(event));
+                self.messages
+                    .retain(|&(retained_key, _)| retained_key != key);
+                self.messages.push_back((key, message));
+            }
+            Retention::UntilCleared => self.messages.push_back((None, message)),
+        }
+    }
+
+    /// Returns the number of retained events.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Returns `true` if there is no retained events.
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Removes all retained events.
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+}
@@ -0,0 +1,83 @@
+use bevy::{
+    ecs::{event::Event, system::SystemParam},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use super::event_channel_mut;
+use crate::{client::LastTick, replicon_core::NetworkTick};
+
+/// An extension trait for [`App`] for including client's tick into client events.
+pub trait ClientEventStampAppExt {
+    /// Includes [`ClientTick`] into each message of already registered client event `T`.
+    ///
+    /// The tick will be available on server in [`FromClient::tick`](super::client_event::FromClient::tick).
+    /// Useful for lag compensation together with [`ComponentHistory`](crate::server::history::ComponentHistory).
+    /// Should be called on both server and clients.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered as client event.
+    fn stamp_client_event<T: Event>(&mut self) -> &mut Self;
+}
+
+impl ClientEventStampAppExt for App {
+    fn stamp_client_event<T: Event>(&mut self) -> &mut Self {
+        event_channel_mut::<T>(self).stamped = true;
+
+        self.init_resource::<InterpolationOffset>()
+    }
+}
+
+/// Server tick that the client was displaying when it sent an event.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ClientTick {
+    /// Last server tick received by the client, see [`LastTick`].
+    pub tick: NetworkTick,
+
+    /// How many ticks behind [`Self::tick`] the client was displaying the world, see [`InterpolationOffset`].
+    pub interpolation_offset: f32,
+}
+
+/// How many ticks behind [`LastTick`] the client displays the world.
+///
+/// Should be updated on client if it interpolates replicated state.
+/// Sent to server as part of [`ClientTick`] with stamped client events.
+#[derive(Clone, Copy, Debug, Default, Deref, DerefMut, Resource)]
+pub struct InterpolationOffset(pub f32);
+
+/// Provides [`ClientTick`] for stamped client events.
+#[derive(SystemParam)]
+pub(super) struct TickStamper<'w> {
+    last_tick: Option<Res<'w, LastTick>>,
+    interpolation_offset: Option<Res<'w, InterpolationOffset>>,
+    network_tick: Option<Res<'w, NetworkTick>>,
+}
+
+impl TickStamper<'_> {
+    /// Returns the tick for events sent by client.
+    pub(super) fn client_tick(&self) -> ClientTick {
+        ClientTick {
+            tick: self
+                .last_tick
+                .as_deref()
+                .map(|last_tick| **last_tick)
+                .unwrap_or_default(),
+            interpolation_offset: self
+                .interpolation_offset
+                .as_deref()
+                .map(|offset| **offset)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the tick for events that server sends to itself.
+    ///
+    /// Server always displays the current state, so the interpolation offset is zero.
+    pub(super) fn local_tick(&self) -> ClientTick {
+        ClientTick {
+            tick: self.network_tick.as_deref().copied().unwrap_or_default(),
+            interpolation_offset: 0.0,
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::{
+    ecs::{event::Event, system::SystemParam},
+    prelude::*,
+};
+
+use super::{client_event::ViolationKind, event_channel_mut};
+use crate::{
+    replicon_core::replication_rules::{MapNetworkEntities, Mapper, Replication},
+    server::rooms::{RoomId, Rooms},
+};
+
+/// An extension trait for [`App`] for validating entities inside mapped client events.
+pub trait ClientEventValidationAppExt {
+    /// Validates entities inside already registered mapped client event `T` on server.
+    ///
+    /// Events that fail validation won't be emitted as [`FromClient<T>`](super::client_event::FromClient),
+    /// [`ClientViolation`](super::client_event::ClientViolation) will be emitted instead.
+    ///
+    /// Should be called on server.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is not registered as client event.
+    fn validate_client_event<T: Event>(&mut self, validation: EntityValidation) -> &mut Self;
+}
+
+impl ClientEventValidationAppExt for App {
+    fn validate_client_event<T: Event>(&mut self, validation: EntityValidation) -> &mut Self {
+        event_channel_mut::<T>(self).validation = Some(validation);
+
+        self
+    }
+}
+
+/// Checks that entities inside a mapped client event can be accessed by the sending client.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EntityValidation {
+    /// All entities should be replicated and visible to the client (see [`Rooms`]).
+    Visible,
+    /// Same as [`EntityValidation::Visible`], but the entities also should be owned
+    /// by the client (see [`EntityOwner`]).
+    Owned,
+}
+
+/// Client that owns the entity.
+///
+/// Used only on server to validate client events with [`EntityValidation::Owned`].
+#[derive(Clone, Component, Copy, Debug, Eq, PartialEq)]
+pub struct EntityOwner(pub u64);
+
+/// Components that define which clients can access an entity.
+type EntityAccess = (Option<&'static RoomId>, Option<&'static EntityOwner>);
+
+/// Validates entities inside received client events.
+#[derive(SystemParam)]
+pub(super) struct EntityValidator<'w, 's> {
+    rooms: Res<'w, Rooms>,
+    entities: Query<'w, 's, EntityAccess, With<Replication>>,
+}
+
+impl EntityValidator<'_, '_> {
+    /// Checks that all entities inside the event pass `validation`.
+    ///
+    /// Returns the violation for the first invalid entity.
+    /// Always succeeds if `validation` is `None`.
+    pub(super) fn validate<T: MapNetworkEntities>(
+        &self,
+        client_id: u64,
+        event: &mut T,
+        validation: Option<EntityValidation>,
+    ) -> Result<(), ViolationKind> {
+        let Some(validation) = validation else {
+            return Ok(());
+        };
+
+        let mut checker = ValidationChecker {
+            client_id,
+            validation,
+            rooms: &self.rooms,
+            entities: &self.entities,
+            violation: None,
+        };
+        event.map_entities(&mut checker);
+        match checker.violation {
+            Some(kind) => Err(kind),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks entities without changing them and remembers the first violation.
+struct ValidationChecker<'a, 'w, 's> {
+    client_id: u64,
+    validation: EntityValidation,
+    rooms: &'a Rooms,
+    entities: &'a Query<'w, 's, EntityAccess, With<Replication>>,
+    violation: Option<ViolationKind>,
+}
+
+impl ValidationChecker<'_, '_, '_> {
+    fn check(&self, entity: Entity) -> Result<(), ViolationKind> {
+        let Ok((room_id, owner)) = self.entities.get(entity) else {
+            return Err(ViolationKind::NotReplicated(entity));
+        };
+        if !self.rooms.is_visible(room_id.copied(), self.client_id) {
+            return Err(ViolationKind::NotVisible(entity));
+        }
+        if self.validation == EntityValidation::Owned
+            && owner.map_or(true, |owner| owner.0 != self.client_id)
+        {
+            return Err(ViolationKind::NotOwned(entity));
+        }
+
+        Ok(())
+    }
+}
+
+impl Mapper for ValidationChecker<'_, '_, '_> {
+    fn map(&mut self, entity: Entity) -> Entity {
+        if self.violation.is_none() {
+            self.violation = self.check(entity).err();
+        }
+        entity
+    }
+}
@@ -1,4 +1,6 @@
+pub mod compression;
 pub mod replication_rules;
+pub mod snapshot;
 
 use std::cmp::Ordering;
 
@@ -6,6 +8,7 @@ use bevy::prelude::*;
 use bevy_renet::renet::{ChannelConfig, SendType};
 use serde::{Deserialize, Serialize};
 
+use compression::NetworkCompression;
 use replication_rules::ReplicationRules;
 
 pub struct RepliconCorePlugin;
@@ -13,6 +16,7 @@ pub struct RepliconCorePlugin;
 impl Plugin for RepliconCorePlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<NetworkChannels>()
+            .init_resource::<NetworkCompression>()
             .init_resource::<ReplicationRules>();
     }
 }
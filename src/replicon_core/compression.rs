@@ -0,0 +1,431 @@
+#[cfg(any(feature = "lz4", feature = "zstd"))]
+use std::io::Cursor;
+use std::mem;
+#[cfg(feature = "zstd")]
+use std::{cell::RefCell, sync::Arc};
+
+use bevy::prelude::*;
+use bevy_renet::renet::Bytes;
+#[cfg(any(feature = "lz4", feature = "zstd"))]
+use bincode::{DefaultOptions, Options};
+
+/// Compression settings for replication messages and events registered with
+/// [`EventCompressionAppExt::compress_event`](crate::network_event::EventCompressionAppExt::compress_event).
+///
+/// Every compressible message starts with a flag byte that tells which algorithm was used,
+/// so the receiving side doesn't need to know the sender's algorithm or threshold.
+/// But if a dictionary is used, it should be identical on server and clients.
+#[derive(Clone, Resource)]
+pub struct NetworkCompression {
+    /// Algorithm used to compress messages.
+    pub algorithm: CompressionAlgorithm,
+
+    /// Messages smaller than this number of bytes are sent uncompressed.
+    ///
+    /// Small messages usually don't compress well and it's not worth spending the CPU time on them.
+    pub threshold: usize,
+
+    /// Messages that claim a larger uncompressed size are rejected without decompression.
+    ///
+    /// Protects from peers that send a tiny message with a huge size to exhaust the memory.
+    pub max_decompressed_size: usize,
+}
+
+impl Default for NetworkCompression {
+    fn default() -> Self {
+        Self {
+            algorithm: Default::default(),
+            threshold: 128,
+            max_decompressed_size: 16 * 1024 * 1024,
+        }
+    }
+}
+
+impl NetworkCompression {
+    /// Creates compression settings with the specified algorithm and the default threshold.
+    pub fn new(algorithm: CompressionAlgorithm) -> Self {
+        Self {
+            algorithm,
+            ..Default::default()
+        }
+    }
+
+    /// Compresses `message` and prepends a flag byte with the used algorithm.
+    ///
+    /// Message is copied as is if it's smaller than [`Self::threshold`] or if compression is disabled.
+    pub fn compress(&self, message: &[u8]) -> Result<Bytes, bincode::Error> {
+        if message.len() < self.threshold {
+            return Ok(uncompressed(message));
+        }
+
+        match &self.algorithm {
+            CompressionAlgorithm::None => Ok(uncompressed(message)),
+            #[cfg(feature = "lz4")]
+            CompressionAlgorithm::Lz4 => {
+                let mut cursor = compressed_header(CompressionFlag::Lz4, message.len())?;
+                cursor
+                    .get_mut()
+                    .extend_from_slice(&lz4_flex::block::compress(message));
+                Ok(cursor.into_inner().into())
+            }
+            #[cfg(feature = "zstd")]
+            CompressionAlgorithm::Zstd { level, dictionary } => {
+                let mut cursor = compressed_header(CompressionFlag::Zstd, message.len())?;
+                let compressed = zstd_compress(*level, dictionary.as_ref(), message)?;
+                cursor.get_mut().extend_from_slice(&compressed);
+                Ok(cursor.into_inner().into())
+            }
+        }
+    }
+
+    /// Reads the flag byte and decompresses `message` with the algorithm from it.
+    ///
+    /// Returns an error if the message was compressed with an algorithm
+    /// whose cargo feature is disabled or its uncompressed size exceeds [`Self::max_decompressed_size`].
+    pub fn decompress(&self, message: Bytes) -> Result<Bytes, bincode::Error> {
+        self.decompress_limited(message, self.max_decompressed_size)
+    }
+
+    /// Like [`Self::decompress`], but rejects messages whose uncompressed size exceeds `max_size`.
+    ///
+    /// The smallest of `max_size` and [`Self::max_decompressed_size`] is used.
+    pub fn decompress_limited(
+        &self,
+        message: Bytes,
+        max_size: usize,
+    ) -> Result<Bytes, bincode::Error> {
+        let max_size = max_size.min(self.max_decompressed_size);
+        let Some(&flag) = message.first() else {
+            return Err(bincode::ErrorKind::Custom(
+                "message should contain compression flag".into(),
+            )
+            .into());
+        };
+
+        match flag {
+            flag if flag == CompressionFlag::None as u8 => {
+                if message.len() - 1 > max_size {
+                    return Err(bincode::ErrorKind::SizeLimit.into());
+                }
+                Ok(message.slice(1..))
+            }
+            #[cfg(feature = "lz4")]
+            flag if flag == CompressionFlag::Lz4 as u8 => {
+                let (len, payload) = read_header(&message, max_size)?;
+                let message = lz4_flex::block::decompress(payload, len)
+                    .map_err(|e| bincode::ErrorKind::Custom(e.to_string()))?;
+                Ok(message.into())
+            }
+            #[cfg(feature = "zstd")]
+            flag if flag == CompressionFlag::Zstd as u8 => {
+                let (len, payload) = read_header(&message, max_size)?;
+                let dictionary = match &self.algorithm {
+                    CompressionAlgorithm::Zstd { dictionary, .. } => dictionary.as_ref(),
+                    _ => None,
+                };
+                let message = zstd_decompress(dictionary, payload, len)?;
+                Ok(message.into())
+            }
+            flag => Err(bincode::ErrorKind::Custom(format!(
+                "unsupported compression flag {flag}, check enabled cargo features"
+            ))
+            .into()),
+        }
+    }
+}
+
+/// Returns uncompressed size of `message` claimed by its header without decompressing it.
+///
+/// [`NetworkCompression::decompress`] never produces a message larger than the claimed size,
+/// so it can be used to reject messages before decompression.
+/// Returns [`None`] if the message has no valid header.
+pub(crate) fn decompressed_len(message: &[u8]) -> Option<usize> {
+    match *message.first()? {
+        flag if flag == CompressionFlag::None as u8 => Some(message.len() - 1),
+        #[cfg(feature = "lz4")]
+        flag if flag == CompressionFlag::Lz4 as u8 => claimed_len(message),
+        #[cfg(feature = "zstd")]
+        flag if flag == CompressionFlag::Zstd as u8 => claimed_len(message),
+        _ => None,
+    }
+}
+
+/// Compression algorithm for [`NetworkCompression`].
+///
+/// Each algorithm except [`CompressionAlgorithm::None`] is available under a cargo feature with the same name.
+#[derive(Clone, Default)]
+pub enum CompressionAlgorithm {
+    /// Messages are sent as is.
+    #[default]
+    None,
+    /// Fast compression with moderate ratio.
+    #[cfg(feature = "lz4")]
+    Lz4,
+    /// Slower compression with better ratio.
+    ///
+    /// Replication messages are small, so a dictionary trained with [`train_zstd_dictionary`]
+    /// on sample traffic usually improves the ratio a lot.
+    #[cfg(feature = "zstd")]
+    Zstd {
+        /// Compression level, `0` means zstd default.
+        level: i32,
+        /// Dictionary that should be identical on server and clients.
+        dictionary: Option<Arc<[u8]>>,
+    },
+}
+
+/// Trains zstd dictionary of up to `max_size` bytes from message samples.
+///
+/// Samples could be collected on server with [`CompressionSamples`].
+#[cfg(feature = "zstd")]
+pub fn train_zstd_dictionary(
+    samples: &[Vec<u8>],
+    max_size: usize,
+) -> Result<Arc<[u8]>, bincode::Error> {
+    let dictionary = zstd::dict::from_samples(samples, max_size)?;
+    Ok(dictionary.into())
+}
+
+/// Collects uncompressed replication messages on server if present.
+///
+/// Could be used to gather sample traffic for `train_zstd_dictionary` from `zstd` feature.
+#[derive(Default, Resource)]
+pub struct CompressionSamples {
+    /// Max number of stored samples, new samples are ignored after reaching it.
+    pub capacity: usize,
+
+    samples: Vec<Vec<u8>>,
+}
+
+impl CompressionSamples {
+    /// Creates an empty storage that collects up to `capacity` samples.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            samples: Default::default(),
+        }
+    }
+
+    /// Stores a copy of `message` if the capacity allows.
+    pub(crate) fn push(&mut self, message: &[u8]) {
+        if self.samples.len() < self.capacity {
+            self.samples.push(message.to_vec());
+        }
+    }
+
+    /// Returns collected samples.
+    #[inline]
+    pub fn samples(&self) -> &[Vec<u8>] {
+        &self.samples
+    }
+
+    /// Removes and returns collected samples.
+    pub fn take(&mut self) -> Vec<Vec<u8>> {
+        mem::take(&mut self.samples)
+    }
+}
+
+/// The first byte of each compressible message.
+#[repr(u8)]
+enum CompressionFlag {
+    None = 0,
+    #[cfg(feature = "lz4")]
+    Lz4 = 1,
+    #[cfg(feature = "zstd")]
+    Zstd = 2,
+}
+
+fn uncompressed(message: &[u8]) -> Bytes {
+    let mut flagged = Vec::with_capacity(message.len() + 1);
+    flagged.push(CompressionFlag::None as u8);
+    flagged.extend_from_slice(message);
+    flagged.into()
+}
+
+/// Writes flag and uncompressed length as varint.
+#[cfg(any(feature = "lz4", feature = "zstd"))]
+fn compressed_header(flag: CompressionFlag, len: usize) -> Result<Cursor<Vec<u8>>, bincode::Error> {
+    let mut cursor = Cursor::new(vec![flag as u8]);
+    cursor.set_position(1);
+    DefaultOptions::new().serialize_into(&mut cursor, &(len as u64))?;
+    Ok(cursor)
+}
+
+/// Reads uncompressed length after the flag and returns it with the compressed payload.
+///
+/// Returns an error if the length exceeds `max_size`.
+#[cfg(any(feature = "lz4", feature = "zstd"))]
+fn read_header(message: &[u8], max_size: usize) -> Result<(usize, &[u8]), bincode::Error> {
+    let mut cursor = Cursor::new(&message[1..]);
+    let len: u64 = DefaultOptions::new().deserialize_from(&mut cursor)?;
+    let len = usize::try_from(len)
+        .ok()
+        .filter(|&len| len <= max_size)
+        .ok_or(bincode::ErrorKind::SizeLimit)?;
+    let payload = &message[1 + cursor.position() as usize..];
+    Ok((len, payload))
+}
+
+/// Reads uncompressed length after the flag, saturating it if it doesn't fit into [`usize`].
+#[cfg(any(feature = "lz4", feature = "zstd"))]
+fn claimed_len(message: &[u8]) -> Option<usize> {
+    let len: u64 = DefaultOptions::new()
+        .deserialize_from(Cursor::new(&message[1..]))
+        .ok()?;
+    Some(usize::try_from(len).unwrap_or(usize::MAX))
+}
+
+/// Compressor with the level and the dictionary it was created for.
+#[cfg(feature = "zstd")]
+type CachedCompressor = (i32, Option<Arc<[u8]>>, zstd::bulk::Compressor<'static>);
+
+/// Decompressor with the dictionary it was created for.
+#[cfg(feature = "zstd")]
+type CachedDecompressor = (Option<Arc<[u8]>>, zstd::bulk::Decompressor<'static>);
+
+#[cfg(feature = "zstd")]
+thread_local! {
+    /// Zstd contexts reused between messages on each thread.
+    ///
+    /// Recreated only if the level or the dictionary changes.
+    static ZSTD_COMPRESSOR: RefCell<Option<CachedCompressor>> = const { RefCell::new(None) };
+    static ZSTD_DECOMPRESSOR: RefCell<Option<CachedDecompressor>> = const { RefCell::new(None) };
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_compress(
+    level: i32,
+    dictionary: Option<&Arc<[u8]>>,
+    message: &[u8],
+) -> Result<Vec<u8>, bincode::Error> {
+    ZSTD_COMPRESSOR.with(|compressor| {
+        let mut compressor = compressor.borrow_mut();
+        let reusable = compressor
+            .as_ref()
+            .is_some_and(|(cached_level, cached_dictionary, _)| {
+                *cached_level == level && same_dictionary(cached_dictionary.as_ref(), dictionary)
+            });
+        if !reusable {
+            let new_compressor = match dictionary {
+                Some(dictionary) => zstd::bulk::Compressor::with_dictionary(level, dictionary)?,
+                None => zstd::bulk::Compressor::new(level)?,
+            };
+            *compressor = Some((level, dictionary.cloned(), new_compressor));
+        }
+
+        let (.., compressor) = compressor.as_mut().expect("compressor should be created");
+        Ok(compressor.compress(message)?)
+    })
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_decompress(
+    dictionary: Option<&Arc<[u8]>>,
+    payload: &[u8],
+    len: usize,
+) -> Result<Vec<u8>, bincode::Error> {
+    ZSTD_DECOMPRESSOR.with(|decompressor| {
+        let mut decompressor = decompressor.borrow_mut();
+        let reusable = decompressor.as_ref().is_some_and(|(cached_dictionary, _)| {
+            same_dictionary(cached_dictionary.as_ref(), dictionary)
+        });
+        if !reusable {
+            let new_decompressor = match dictionary {
+                Some(dictionary) => zstd::bulk::Decompressor::with_dictionary(dictionary)?,
+                None => zstd::bulk::Decompressor::new()?,
+            };
+            *decompressor = Some((dictionary.cloned(), new_decompressor));
+        }
+
+        let (_, decompressor) = decompressor
+            .as_mut()
+            .expect("decompressor should be created");
+        Ok(decompressor.decompress(payload, len)?)
+    })
+}
+
+#[cfg(feature = "zstd")]
+fn same_dictionary(a: Option<&Arc<[u8]>>, b: Option<&Arc<[u8]>>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn below_threshold() {
+        let compression = NetworkCompression::default();
+        let message = vec![1; compression.threshold - 1];
+        let compressed = compression.compress(&message).unwrap();
+        assert_eq!(compressed[0], CompressionFlag::None as u8);
+        assert_eq!(compression.decompress(compressed).unwrap(), message);
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn lz4() {
+        let compression = NetworkCompression::new(CompressionAlgorithm::Lz4);
+        let message = vec![1; compression.threshold * 4];
+        let compressed = compression.compress(&message).unwrap();
+        assert_eq!(compressed[0], CompressionFlag::Lz4 as u8);
+        assert!(compressed.len() < message.len());
+        assert_eq!(compression.decompress(compressed).unwrap(), message);
+    }
+
+    #[test]
+    fn uncompressed_size_limit() {
+        let compression = NetworkCompression::default();
+        let message = vec![1; 16];
+        let compressed = compression.compress(&message).unwrap();
+        assert!(compression.decompress_limited(compressed, 15).is_err());
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn lz4_size_limit() {
+        let compression = NetworkCompression::new(CompressionAlgorithm::Lz4);
+        let mut message = compressed_header(CompressionFlag::Lz4, usize::MAX)
+            .unwrap()
+            .into_inner();
+        message.push(0);
+        assert!(
+            compression.decompress(message.into()).is_err(),
+            "message with a huge claimed length should be rejected before decompression"
+        );
+
+        let message = vec![1; compression.threshold * 4];
+        let compressed = compression.compress(&message).unwrap();
+        assert!(compression
+            .decompress_limited(compressed.clone(), message.len() - 1)
+            .is_err());
+        assert_eq!(
+            compression
+                .decompress_limited(compressed, message.len())
+                .unwrap(),
+            message
+        );
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_with_dictionary() {
+        let samples: Vec<Vec<u8>> = (0..100u8)
+            .map(|index| [index, 1, 2, 3, 4, 5, 6, 7].repeat(16))
+            .collect();
+        let dictionary = train_zstd_dictionary(&samples, 1024).unwrap();
+        let compression = NetworkCompression::new(CompressionAlgorithm::Zstd {
+            level: 0,
+            dictionary: Some(dictionary),
+        });
+        let message = &samples[0];
+        let compressed = compression.compress(message).unwrap();
+        assert_eq!(compressed[0], CompressionFlag::Zstd as u8);
+        assert!(compressed.len() < message.len());
+        assert_eq!(compression.decompress(compressed).unwrap(), *message);
+    }
+}
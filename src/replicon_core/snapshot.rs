@@ -0,0 +1,188 @@
+use std::{io::Cursor, ops::Range};
+
+use bevy::{ecs::world::EntityRef, prelude::*, utils::HashSet};
+use bevy_renet::renet::{Bytes, RenetClient};
+
+use super::{
+    replication_rules::{Replication, ReplicationId, ReplicationRules},
+    NetworkTick,
+};
+use crate::client::{LastTick, NetworkEntityMap};
+
+/// Serialized state of replicated entities at a point in time.
+///
+/// Components are serialized using functions from [`ReplicationRules`], so they don't
+/// need to implement [`Reflect`]. Useful for prediction, lag compensation or undo.
+#[derive(Clone)]
+pub struct ReplicationSnapshot {
+    tick: NetworkTick,
+    entities: Vec<EntitySnapshot>,
+    data: Bytes,
+}
+
+impl ReplicationSnapshot {
+    /// Captures all replicated components of all replicated entities.
+    pub fn capture(world: &World) -> Result<Self, bincode::Error> {
+        Self::capture_filtered(world, |_| true)
+    }
+
+    /// Like [`Self::capture`], but only for entities for which `filter` returns `true`.
+    pub fn capture_filtered(
+        world: &World,
+        mut filter: impl FnMut(&EntityRef) -> bool,
+    ) -> Result<Self, bincode::Error> {
+        let replication_rules = world.resource::<ReplicationRules>();
+        let entity_map = world.get_resource::<NetworkEntityMap>();
+        let mut cursor = Cursor::new(Vec::new());
+        let mut entities = Vec::new();
+        for entity in world
+            .iter_entities()
+            .filter(|entity| entity.contains_id(replication_rules.get_marker_id()))
+            .filter(|entity| filter(entity))
+        {
+            let mut components = Vec::new();
+            for component_id in entity.archetype().components() {
+                let Some((replication_id, replication_info)) = replication_rules.get(component_id)
+                else {
+                    continue;
+                };
+                if entity.contains_id(replication_info.ignored_id) {
+                    continue;
+                }
+
+                let component = entity
+                    .get_by_id(component_id)
+                    .unwrap_or_else(|| panic!("{:?} should have {component_id:?}", entity.id()));
+                let start = cursor.position() as usize;
+                (replication_info.serialize)(component, &mut cursor)?;
+                components.push((replication_id, start..cursor.position() as usize));
+            }
+            entities.push(EntitySnapshot {
+                entity: entity.id(),
+                server_entity: entity_map
+                    .and_then(|entity_map| entity_map.to_server().get(&entity.id()).copied()),
+                components,
+            });
+        }
+
+        let tick = if world.contains_resource::<RenetClient>() {
+            **world.resource::<LastTick>()
+        } else {
+            world
+                .get_resource::<NetworkTick>()
+                .copied()
+                .unwrap_or_default()
+        };
+
+        Ok(Self {
+            tick,
+            entities,
+            data: cursor.into_inner().into(),
+        })
+    }
+
+    /// Restores captured components.
+    ///
+    /// Replicated components that were inserted after the capture are removed.
+    /// Entities that were despawned after the capture are spawned again with new IDs and references
+    /// to them inside mapped components are updated. On client respawned entities are also registered
+    /// in [`NetworkEntityMap`], so they continue to receive updates from server.
+    /// Entities that were spawned after the capture are left untouched.
+    ///
+    /// References to entities that neither exist nor were captured are replaced with [`Entity::PLACEHOLDER`].
+    ///
+    /// Components are restored using deserialization and removal functions from [`ReplicationRules`]
+    /// with the captured tick.
+    pub fn restore(&self, world: &mut World) -> Result<(), bincode::Error> {
+        world.resource_scope(|world, replication_rules: Mut<ReplicationRules>| {
+            // Entities inside components are local, so only respawned entities need a mapping.
+            let mut entity_map = NetworkEntityMap::local();
+            for &EntitySnapshot {
+                entity,
+                server_entity,
+                ..
+            } in &self.entities
+            {
+                if world.get_entity(entity).is_none() {
+                    let new_entity = world.spawn(Replication).id();
+                    entity_map.insert(entity, new_entity);
+                    if let (Some(server_entity), Some(mut network_entity_map)) =
+                        (server_entity, world.get_resource_mut::<NetworkEntityMap>())
+                    {
+                        network_entity_map.remove_by_server(server_entity);
+                        network_entity_map.insert(server_entity, new_entity);
+                    }
+                }
+            }
+
+            for EntitySnapshot {
+                entity, components, ..
+            } in &self.entities
+            {
+                let entity = entity_map
+                    .to_client()
+                    .get(entity)
+                    .copied()
+                    .unwrap_or(*entity);
+                let mut entity = world.entity_mut(entity);
+
+                let captured_ids: HashSet<_> = components
+                    .iter()
+                    .map(|&(replication_id, _)| replication_id)
+                    .collect();
+                let inserted_ids: Vec<_> = entity
+                    .archetype()
+                    .components()
+                    .filter_map(|component_id| replication_rules.get(component_id))
+                    .filter(|(replication_id, replication_info)| {
+                        !captured_ids.contains(replication_id)
+                            && !entity.contains_id(replication_info.ignored_id)
+                    })
+                    .map(|(replication_id, _)| replication_id)
+                    .collect();
+                for replication_id in inserted_ids {
+                    // SAFETY: `replication_id` obtained from the same replication rules.
+                    let replication_info =
+                        unsafe { replication_rules.get_info_unchecked(replication_id) };
+                    (replication_info.remove)(&mut entity, self.tick);
+                }
+
+                for (replication_id, range) in components {
+                    // SAFETY: `replication_id` obtained from the same replication rules.
+                    let replication_info =
+                        unsafe { replication_rules.get_info_unchecked(*replication_id) };
+                    let mut cursor = Cursor::new(self.data.slice(range.clone()));
+                    (replication_info.deserialize)(
+                        &mut entity,
+                        &mut entity_map,
+                        &mut cursor,
+                        self.tick,
+                    )?;
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Returns the tick on which the snapshot was captured.
+    ///
+    /// [`NetworkTick`] on server or [`LastTick`] on client.
+    pub fn tick(&self) -> NetworkTick {
+        self.tick
+    }
+
+    /// Returns captured entities.
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.iter().map(|snapshot| snapshot.entity)
+    }
+}
+
+#[derive(Clone)]
+struct EntitySnapshot {
+    entity: Entity,
+    /// Server entity if captured on client.
+    server_entity: Option<Entity>,
+    /// Replication IDs of captured components with ranges of their serialized data.
+    components: Vec<(ReplicationId, Range<usize>)>,
+}
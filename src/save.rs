@@ -0,0 +1,316 @@
+pub mod autosave;
+pub mod migration;
+
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io::{Cursor, Read, Write},
+};
+
+use bevy::{
+    ecs::{entity::EntityMap, world::EntityRef},
+    prelude::*,
+    utils::HashMap,
+};
+use bevy_renet::renet::Bytes;
+use bincode::{DefaultOptions, Options};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    client::NetworkEntityMap,
+    replicon_core::{
+        replication_rules::{Replication, ReplicationId, ReplicationRules},
+        NetworkTick,
+    },
+};
+use migration::{MigrateFn, SaveMigrations};
+
+/// Binary save of replicated entities.
+///
+/// Components are serialized using functions from [`ReplicationRules`], so unlike
+/// [`replicate_into_scene`](crate::server::replicate_into_scene) they don't need to implement [`Reflect`].
+/// The save starts with a header of component type names, so it doesn't depend on the order
+/// in which components were registered for replication.
+///
+/// Each save records the version from [`SaveMigrations`] to upgrade old saves on load.
+/// See [`SaveMigrationAppExt`](migration::SaveMigrationAppExt) for details.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct WorldSave {
+    version: u32,
+    tick: NetworkTick,
+
+    /// Type names of saved components.
+    ///
+    /// Components of saved entities refer to them by index.
+    components: Vec<String>,
+
+    entities: Vec<SavedEntity>,
+}
+
+impl WorldSave {
+    /// Saves all replicated components of all replicated entities.
+    pub fn capture(world: &World) -> Result<Self, bincode::Error> {
+        Self::capture_filtered(world, |_| true)
+    }
+
+    /// Like [`Self::capture`], but only for entities for which `filter` returns `true`.
+    pub fn capture_filtered(
+        world: &World,
+        mut filter: impl FnMut(&EntityRef) -> bool,
+    ) -> Result<Self, bincode::Error> {
+        let replication_rules = world.resource::<ReplicationRules>();
+        let mut components = Vec::new();
+        let mut indices = HashMap::default();
+        let mut entities = Vec::new();
+        for entity in world
+            .iter_entities()
+            .filter(|entity| entity.contains_id(replication_rules.get_marker_id()))
+            .filter(|entity| filter(entity))
+        {
+            let mut saved_components = Vec::new();
+            for component_id in entity.archetype().components() {
+                let Some((_, replication_info)) = replication_rules.get(component_id) else {
+                    continue;
+                };
+                if entity.contains_id(replication_info.ignored_id) {
+                    continue;
+                }
+
+                let index = *indices.entry(component_id).or_insert_with(|| {
+                    // SAFETY: `component_id` obtained from the world.
+                    let component_info =
+                        unsafe { world.components().get_info_unchecked(component_id) };
+                    components.push(component_info.name().to_string());
+                    components.len() - 1
+                });
+
+                let component = entity
+                    .get_by_id(component_id)
+                    .unwrap_or_else(|| panic!("{:?} should have {component_id:?}", entity.id()));
+                let mut cursor = Cursor::new(Vec::new());
+                (replication_info.serialize)(component, &mut cursor)?;
+                saved_components.push((index, cursor.into_inner()));
+            }
+            entities.push(SavedEntity {
+                entity: entity.id(),
+                components: saved_components,
+            });
+        }
+
+        let version = world
+            .get_resource::<SaveMigrations>()
+            .map_or(0, |save_migrations| save_migrations.version());
+        let tick = world
+            .get_resource::<NetworkTick>()
+            .copied()
+            .unwrap_or_default();
+
+        Ok(Self {
+            version,
+            tick,
+            components,
+            entities,
+        })
+    }
+
+    /// Spawns saved entities and marks them for replication.
+    ///
+    /// Entity references inside mapped components are updated to the spawned entities.
+    /// References to entities that are not in the save are mapped to new empty entities.
+    /// Connected clients receive loaded entities as new spawns with the next tick.
+    ///
+    /// Component names and data from older saves are upgraded using [`SaveMigrations`].
+    ///
+    /// Returns mapping from saved entities to the spawned entities.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the save version is newer than the current one, any saved component
+    /// is not registered for replication or its data can't be migrated or deserialized.
+    /// Entities spawned before the error are kept.
+    pub fn load(&self, world: &mut World) -> Result<EntityMap, SaveError> {
+        world.resource_scope(|world, replication_rules: Mut<ReplicationRules>| {
+            let components = self.resolve_components(world, &replication_rules)?;
+
+            // Make sure inserted components are newer than the last registered system tick
+            // even if no systems have run since the last replication.
+            world.increment_change_tick();
+
+            let mut entity_map = NetworkEntityMap::default();
+            for saved_entity in &self.entities {
+                let entity = world.spawn(Replication).id();
+                entity_map.insert(saved_entity.entity, entity);
+            }
+
+            for SavedEntity {
+                entity,
+                components: saved_components,
+            } in &self.entities
+            {
+                let entity = entity_map.to_client()[entity];
+                let mut entity = world.entity_mut(entity);
+                for (index, bytes) in saved_components {
+                    let (replication_id, migrations) = components
+                        .get(*index)
+                        .ok_or(SaveError::InvalidIndex(*index))?;
+                    let mut bytes = Cow::Borrowed(bytes.as_slice());
+                    for migrate in migrations {
+                        bytes = Cow::Owned((migrate)(&bytes)?);
+                    }
+
+                    // SAFETY: `replication_id` obtained from the same replication rules.
+                    let replication_info =
+                        unsafe { replication_rules.get_info_unchecked(*replication_id) };
+                    let mut cursor = Cursor::new(Bytes::copy_from_slice(&bytes));
+                    (replication_info.deserialize)(
+                        &mut entity,
+                        &mut entity_map,
+                        &mut cursor,
+                        self.tick,
+                    )?;
+                }
+            }
+
+            let mut loaded_entities = EntityMap::default();
+            for saved_entity in &self.entities {
+                loaded_entities.insert(
+                    saved_entity.entity,
+                    entity_map.to_client()[&saved_entity.entity],
+                );
+            }
+
+            Ok(loaded_entities)
+        })
+    }
+
+    /// Returns replication IDs and pending migrations for saved components.
+    fn resolve_components(
+        &self,
+        world: &World,
+        replication_rules: &ReplicationRules,
+    ) -> Result<Vec<(ReplicationId, Vec<MigrateFn>)>, SaveError> {
+        let default_migrations = SaveMigrations::default();
+        let save_migrations = world
+            .get_resource::<SaveMigrations>()
+            .unwrap_or(&default_migrations);
+        if self.version > save_migrations.version() {
+            return Err(SaveError::UnsupportedVersion(self.version));
+        }
+
+        let replication_ids: HashMap<_, _> = replication_rules
+            .get_ids()
+            .iter()
+            .filter_map(|(&component_id, &replication_id)| {
+                let component_info = world.components().get_info(component_id)?;
+                Some((component_info.name(), replication_id))
+            })
+            .collect();
+
+        self.components
+            .iter()
+            .map(|type_name| {
+                let current_name = save_migrations.current_name(type_name);
+                let replication_id = replication_ids
+                    .get(current_name)
+                    .copied()
+                    .ok_or_else(|| SaveError::UnknownComponent(type_name.clone()))?;
+                let migrations = save_migrations
+                    .migrations(current_name, self.version)
+                    .collect();
+                Ok((replication_id, migrations))
+            })
+            .collect()
+    }
+
+    /// Writes the save in binary format.
+    pub fn write(&self, writer: impl Write) -> Result<(), bincode::Error> {
+        DefaultOptions::new().serialize_into(writer, self)
+    }
+
+    /// Reads the save written by [`Self::write`].
+    pub fn read(reader: impl Read) -> Result<Self, bincode::Error> {
+        DefaultOptions::new().deserialize_from(reader)
+    }
+
+    /// Returns version from [`SaveMigrations`] with which the save was created.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns [`NetworkTick`] on which the save was captured.
+    pub fn tick(&self) -> NetworkTick {
+        self.tick
+    }
+
+    /// Returns type names of saved components.
+    pub fn components(&self) -> &[String] {
+        &self.components
+    }
+
+    /// Returns saved entities.
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.entities.iter().map(|saved_entity| saved_entity.entity)
+    }
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+struct SavedEntity {
+    entity: Entity,
+
+    /// Indices of component type names with serialized data.
+    components: Vec<(usize, Vec<u8>)>,
+}
+
+/// Error that can happen on [`WorldSave::load`].
+#[derive(Debug)]
+pub enum SaveError {
+    /// Save was created with a newer version than [`SaveMigrations::version`].
+    UnsupportedVersion(u32),
+
+    /// Saved component with this type name is not registered for replication.
+    UnknownComponent(String),
+
+    /// Saved entity refers to a component type name that is not in the header.
+    InvalidIndex(usize),
+
+    /// Component data can't be migrated or deserialized.
+    Bincode(bincode::Error),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::UnsupportedVersion(version) => {
+                write!(f, "save version {version} is newer than the current one")
+            }
+            SaveError::UnknownComponent(type_name) => {
+                write!(
+                    f,
+                    "component `{type_name}` is not registered for replication"
+                )
+            }
+            SaveError::InvalidIndex(index) => {
+                write!(f, "component index {index} is missing in the header")
+            }
+            SaveError::Bincode(error) => {
+                write!(f, "unable to migrate or deserialize component: {error}")
+            }
+        }
+    }
+}
+
+impl Error for SaveError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SaveError::Bincode(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<bincode::Error> for SaveError {
+    fn from(error: bincode::Error) -> Self {
+        Self::Bincode(error)
+    }
+}
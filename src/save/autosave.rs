@@ -0,0 +1,200 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{IoTaskPool, Task},
+    time::common_conditions::on_timer,
+};
+use bincode::{DefaultOptions, Options};
+
+use super::WorldSave;
+use crate::server::has_authority;
+
+/// Number of files between which saves alternate.
+const SLOTS_COUNT: usize = 2;
+
+/// Periodically saves all replicated entities to disk.
+///
+/// Saves are captured using [`WorldSave`] and written on [`IoTaskPool`].
+/// Writes alternate between two files next to the specified path (with `.0` and `.1` suffixes),
+/// each written to a temporary file first and then renamed. So if the server crashes during
+/// writing, the previous save remains intact.
+///
+/// Each save contains the whole replicated state. Use [`Autosave::latest`] on startup to restore it.
+///
+/// Used only on server or in single-player.
+pub struct AutosavePlugin {
+    path: PathBuf,
+    interval: Duration,
+}
+
+impl AutosavePlugin {
+    /// Creates plugin that saves to files next to `path` with the specified interval.
+    pub fn new(path: impl Into<PathBuf>, interval: Duration) -> Self {
+        Self {
+            path: path.into(),
+            interval,
+        }
+    }
+}
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Autosave::new(self.path.clone()))
+            .add_systems(
+                Last,
+                Self::saving_system
+                    .run_if(has_authority())
+                    .run_if(on_timer(self.interval)),
+            );
+    }
+}
+
+impl AutosavePlugin {
+    fn saving_system(world: &mut World) {
+        if world.resource::<Autosave>().is_saving() {
+            debug!("skipping autosave because the previous one is still in progress");
+            return;
+        }
+
+        let world_save = match WorldSave::capture(world) {
+            Ok(world_save) => world_save,
+            Err(e) => {
+                error!("unable to capture autosave: {e}");
+                return;
+            }
+        };
+
+        let mut autosave = world.resource_mut::<Autosave>();
+        let sequence = autosave.next_sequence;
+        autosave.next_sequence += 1;
+        let path = slot_path(&autosave.path, sequence);
+        autosave.task = Some(IoTaskPool::get().spawn(async move {
+            if let Err(e) = write_slot(&path, sequence, &world_save) {
+                error!("unable to write autosave to {path:?}: {e}");
+            }
+        }));
+    }
+}
+
+/// State of [`AutosavePlugin`].
+#[derive(Resource)]
+pub struct Autosave {
+    path: PathBuf,
+
+    /// Sequence number of the next save.
+    ///
+    /// Used to find the latest save and to choose a file to write.
+    next_sequence: u64,
+
+    /// Currently running write.
+    task: Option<Task<()>>,
+}
+
+impl Autosave {
+    fn new(path: PathBuf) -> Self {
+        let next_sequence = (0..SLOTS_COUNT as u64)
+            .filter_map(|slot| read_sequence(&slot_path(&path, slot)).ok())
+            .max()
+            .map_or(0, |sequence| sequence + 1);
+
+        Self {
+            path,
+            next_sequence,
+            task: None,
+        }
+    }
+
+    /// Reads the latest successfully written save.
+    ///
+    /// Returns [`None`] if there are no saves. If the latest save is corrupted,
+    /// the previous one is returned instead.
+    pub fn latest(&self) -> Result<Option<WorldSave>, bincode::Error> {
+        read_latest(&self.path).map(|latest| latest.map(|(_, world_save)| world_save))
+    }
+
+    /// Returns path next to which saves are written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns `true` if a save is currently being written.
+    pub fn is_saving(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+}
+
+/// Returns path of the file into which the save with `sequence` should be written.
+fn slot_path(path: &Path, sequence: u64) -> PathBuf {
+    let mut slot_path = OsString::from(path);
+    slot_path.push(format!(".{}", sequence % SLOTS_COUNT as u64));
+    slot_path.into()
+}
+
+fn write_slot(path: &Path, sequence: u64, world_save: &WorldSave) -> bincode::Result<()> {
+    let mut tmp_path = OsString::from(path);
+    tmp_path.push(".tmp");
+
+    let mut writer = BufWriter::new(File::create(&tmp_path)?);
+    DefaultOptions::new().serialize_into(&mut writer, &sequence)?;
+    world_save.write(&mut writer)?;
+    let file = writer.into_inner().map_err(|e| e.into_error())?;
+    file.sync_all()?;
+    fs::rename(tmp_path, path)?;
+
+    Ok(())
+}
+
+/// Reads only the sequence number from the beginning of the slot.
+fn read_sequence(path: &Path) -> bincode::Result<u64> {
+    let reader = BufReader::new(File::open(path)?);
+    DefaultOptions::new().deserialize_from(reader)
+}
+
+fn read_slot(path: &Path) -> bincode::Result<(u64, WorldSave)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let sequence = DefaultOptions::new().deserialize_from(&mut reader)?;
+    let world_save = WorldSave::read(reader)?;
+
+    Ok((sequence, world_save))
+}
+
+/// Reads all existing slots and returns the save with the highest sequence number.
+///
+/// Returns an error only if all existing slots can't be read.
+fn read_latest(path: &Path) -> bincode::Result<Option<(u64, WorldSave)>> {
+    let mut latest: Option<(u64, WorldSave)> = None;
+    let mut last_error = None;
+    for slot in 0..SLOTS_COUNT as u64 {
+        let slot_path = slot_path(path, slot);
+        if !slot_path.exists() {
+            continue;
+        }
+
+        match read_slot(&slot_path) {
+            Ok((sequence, world_save)) => {
+                if latest
+                    .as_ref()
+                    .map_or(true, |&(latest_sequence, _)| sequence > latest_sequence)
+                {
+                    latest = Some((sequence, world_save));
+                }
+            }
+            Err(e) => {
+                warn!("unable to read autosave from {slot_path:?}: {e}");
+                last_error = Some(e);
+            }
+        }
+    }
+
+    match (latest, last_error) {
+        (None, Some(e)) => Err(e),
+        (latest, _) => Ok(latest),
+    }
+}
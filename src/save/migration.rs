@@ -0,0 +1,119 @@
+use std::any;
+
+use bevy::{prelude::*, utils::HashMap};
+
+/// An extension trait for [`App`] for versioning of [`WorldSave`](super::WorldSave).
+pub trait SaveMigrationAppExt {
+    /// Sets version that will be recorded in new saves.
+    ///
+    /// Saves with a greater version can't be loaded. Defaults to 0.
+    fn set_save_version(&mut self, version: u32) -> &mut Self;
+
+    /// Loads component saved under `old_name` as `C`.
+    ///
+    /// Use it after renaming a component or moving it to another module.
+    /// Saved name is the full type name, as returned by [`std::any::type_name`].
+    fn rename_saved_component<C: Component>(&mut self, old_name: impl Into<String>) -> &mut Self;
+
+    /// Upgrades saved data of `C` for saves with a version less than `version`.
+    ///
+    /// Use it after changing the component layout. Migrations are applied in ascending
+    /// order of their versions before deserialization.
+    ///
+    /// Should be called after [`Self::set_save_version`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `version` is greater than the current save version, because new saves
+    /// would be recorded with the old layout and never migrated.
+    fn migrate_saved_component<C: Component>(
+        &mut self,
+        version: u32,
+        migrate: MigrateFn,
+    ) -> &mut Self;
+}
+
+impl SaveMigrationAppExt for App {
+    fn set_save_version(&mut self, version: u32) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(SaveMigrations::default)
+            .version = version;
+        self
+    }
+
+    fn rename_saved_component<C: Component>(&mut self, old_name: impl Into<String>) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(SaveMigrations::default)
+            .renames
+            .insert(old_name.into(), any::type_name::<C>());
+        self
+    }
+
+    fn migrate_saved_component<C: Component>(
+        &mut self,
+        version: u32,
+        migrate: MigrateFn,
+    ) -> &mut Self {
+        let mut save_migrations = self
+            .world
+            .get_resource_or_insert_with(SaveMigrations::default);
+        assert!(
+            version <= save_migrations.version,
+            "migration version {version} for `{}` should not be greater than the current save version {}",
+            any::type_name::<C>(),
+            save_migrations.version
+        );
+        let migrations = save_migrations
+            .migrations
+            .entry(any::type_name::<C>())
+            .or_default();
+        migrations.push((version, migrate));
+        migrations.sort_by_key(|&(version, _)| version);
+        self
+    }
+}
+
+/// Signature of saved component migration functions.
+///
+/// Accepts component data serialized in the previous layout and returns it in the new layout.
+pub type MigrateFn = fn(&[u8]) -> Result<Vec<u8>, bincode::Error>;
+
+/// Current save version and registered migrations.
+///
+/// Created by methods of [`SaveMigrationAppExt`].
+#[derive(Resource, Default)]
+pub struct SaveMigrations {
+    version: u32,
+
+    /// Maps old component names to their current names.
+    renames: HashMap<String, &'static str>,
+
+    /// Migrations for each component name sorted by version.
+    migrations: HashMap<&'static str, Vec<(u32, MigrateFn)>>,
+}
+
+impl SaveMigrations {
+    /// Returns version that is recorded in new saves.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Returns the current name of the saved component.
+    pub(super) fn current_name<'a>(&'a self, type_name: &'a str) -> &'a str {
+        self.renames.get(type_name).copied().unwrap_or(type_name)
+    }
+
+    /// Returns migrations of the component that should be applied to a save with `version`.
+    pub(super) fn migrations(
+        &self,
+        type_name: &str,
+        version: u32,
+    ) -> impl Iterator<Item = MigrateFn> + '_ {
+        self.migrations
+            .get(type_name)
+            .into_iter()
+            .flatten()
+            .filter(move |&&(migration_version, _)| migration_version > version)
+            .map(|&(_, migrate)| migrate)
+    }
+}
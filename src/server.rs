@@ -1,33 +1,40 @@
 pub(super) mod despawn_tracker;
+pub mod history;
 pub(super) mod removal_tracker;
+pub mod rooms;
+pub mod send_rate;
 
 use std::{io::Cursor, mem, time::Duration};
 
 use bevy::{
     ecs::{
         archetype::ArchetypeId,
-        component::{StorageType, Tick},
+        component::{ComponentTicks, StorageType, Tick},
+        entity::EntityMap,
         system::SystemChangeTick,
     },
     prelude::*,
     ptr::Ptr,
-    scene::DynamicEntity,
+    scene::{DynamicEntity, SceneSpawnError},
     time::common_conditions::on_timer,
     utils::HashMap,
 };
 use bevy_renet::{
-    renet::{Bytes, RenetClient, RenetServer, ServerEvent},
+    renet::{RenetClient, RenetServer, ServerEvent},
     transport::NetcodeServerPlugin,
     RenetServerPlugin,
 };
 use bincode::{DefaultOptions, Options};
 
 use crate::replicon_core::{
-    replication_rules::{ReplicationId, ReplicationInfo, ReplicationRules},
+    compression::{CompressionSamples, NetworkCompression},
+    replication_rules::{Replication, ReplicationId, ReplicationInfo, ReplicationRules},
     NetworkTick, REPLICATION_CHANNEL_ID,
 };
 use despawn_tracker::{DespawnTracker, DespawnTrackerPlugin};
 use removal_tracker::{RemovalTracker, RemovalTrackerPlugin};
+use rooms::{RoomId, RoomVisibility, Rooms, RoomsPlugin};
+use send_rate::{ClientSendRates, SendRatePlugin};
 
 pub const SERVER_ID: u64 = 0;
 
@@ -50,8 +57,11 @@ impl Plugin for ServerPlugin {
             NetcodeServerPlugin,
             RemovalTrackerPlugin,
             DespawnTrackerPlugin,
+            RoomsPlugin,
+            SendRatePlugin,
         ))
         .init_resource::<AckedTicks>()
+        .init_resource::<ReplicatedClients>()
         .init_resource::<NetworkTick>()
         .configure_set(
             PreUpdate,
@@ -63,6 +73,12 @@ impl Plugin for ServerPlugin {
                 .before(NetcodeServerPlugin::send_packets)
                 .run_if(resource_changed::<NetworkTick>()),
         )
+        .configure_set(
+            PostUpdate,
+            ServerSet::SendEvents
+                .after(ServerSet::Send)
+                .before(NetcodeServerPlugin::send_packets),
+        )
         .add_systems(
             PreUpdate,
             (Self::acks_receiving_system, Self::acks_cleanup_system)
@@ -82,7 +98,7 @@ impl Plugin for ServerPlugin {
 
         match self.tick_policy {
             TickPolicy::MaxTickRate(max_tick_rate) => {
-                let tick_time = Duration::from_millis(1000 / max_tick_rate as u64);
+                let tick_time = Duration::from_secs(1) / max_tick_rate as u32;
                 app.add_systems(
                     PostUpdate,
                     Self::increment_tick
@@ -96,6 +112,9 @@ impl Plugin for ServerPlugin {
                     Self::increment_tick.before(Self::diffs_sending_system),
                 );
             }
+            TickPolicy::FixedUpdate => {
+                app.add_systems(FixedUpdate, Self::increment_tick);
+            }
             TickPolicy::Manual => (),
         }
     }
@@ -145,28 +164,49 @@ impl ServerPlugin {
         }
     }
 
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
     fn diffs_sending_system(
         mut buffers: Local<Vec<ReplicationBuffer>>,
         change_tick: SystemChangeTick,
-        mut set: ParamSet<(&World, ResMut<RenetServer>, ResMut<AckedTicks>)>,
+        mut set: ParamSet<(
+            &World,
+            ResMut<RenetServer>,
+            ResMut<AckedTicks>,
+            Option<ResMut<CompressionSamples>>,
+            ResMut<ReplicatedClients>,
+        )>,
         replication_rules: Res<ReplicationRules>,
         despawn_tracker: Res<DespawnTracker>,
         network_tick: Res<NetworkTick>,
-        removal_trackers: Query<(Entity, &RemovalTracker)>,
+        compression: Res<NetworkCompression>,
+        send_rates: Res<ClientSendRates>,
+        removal_trackers: Query<(Entity, &RemovalTracker, Option<&RoomId>)>,
+        room_visibilities: Query<(Entity, &RoomVisibility)>,
     ) -> Result<(), bincode::Error> {
         let mut acked_ticks = set.p2();
         acked_ticks.register_network_tick(*network_tick, change_tick.this_run());
 
-        let buffers = prepare_buffers(&mut buffers, &acked_ticks, *network_tick)?;
+        let buffers = prepare_buffers(&mut buffers, &acked_ticks, &send_rates, *network_tick)?;
         collect_changes(
             buffers,
             set.p0(),
             change_tick.this_run(),
             &replication_rules,
         )?;
-        collect_removals(buffers, &removal_trackers, change_tick.this_run())?;
-        collect_despawns(buffers, &despawn_tracker, change_tick.this_run())?;
+        collect_removals(
+            buffers,
+            &removal_trackers,
+            set.p0().resource::<Rooms>(),
+            change_tick.this_run(),
+        )?;
+        collect_despawns(
+            buffers,
+            &despawn_tracker,
+            &room_visibilities,
+            change_tick.this_run(),
+        )?;
 
+        set.p4().0.clear();
         for buffer in buffers {
             debug_assert_eq!(buffer.array_len, 0);
             debug_assert_eq!(buffer.entity_data_len, 0);
@@ -174,24 +214,29 @@ impl ServerPlugin {
             if buffer.arrays_with_data > 0 {
                 buffer.trim_empty_arrays();
 
-                set.p1().send_message(
-                    buffer.client_id,
-                    REPLICATION_CHANNEL_ID,
-                    Bytes::copy_from_slice(buffer.message.get_ref()),
-                );
+                if let Some(mut compression_samples) = set.p3() {
+                    compression_samples.push(buffer.message.get_ref());
+                }
+
+                let message = compression.compress(buffer.message.get_ref())?;
+                set.p1()
+                    .send_message(buffer.client_id, REPLICATION_CHANNEL_ID, message);
+                set.p4().0.push(buffer.client_id);
             }
         }
 
         Ok(())
     }
 
-    fn reset_system(mut acked_ticks: ResMut<AckedTicks>) {
+    fn reset_system(mut acked_ticks: ResMut<AckedTicks>, mut send_rates: ResMut<ClientSendRates>) {
         acked_ticks.clients.clear();
         acked_ticks.system_ticks.clear();
+        send_rates.clear();
     }
 }
 
-/// Initializes buffer for each client and returns it as mutable slice.
+/// Initializes buffer for each client that should receive an update on this tick
+/// and returns it as mutable slice.
 ///
 /// Reuses already allocated buffers.
 /// Creates new buffers if number of clients is bigger then the number of allocated buffers.
@@ -200,10 +245,18 @@ impl ServerPlugin {
 fn prepare_buffers<'a>(
     buffers: &'a mut Vec<ReplicationBuffer>,
     acked_ticks: &AckedTicks,
+    send_rates: &ClientSendRates,
     network_tick: NetworkTick,
 ) -> Result<&'a mut [ReplicationBuffer], bincode::Error> {
     buffers.reserve(acked_ticks.clients.len());
-    for (index, (&client_id, &tick)) in acked_ticks.clients.iter().enumerate() {
+    let mut clients_count = 0;
+    for (index, (&client_id, &tick)) in acked_ticks
+        .clients
+        .iter()
+        .filter(|(&client_id, _)| send_rates.is_due(client_id))
+        .enumerate()
+    {
+        clients_count += 1;
         let system_tick = *acked_ticks.system_ticks.get(&tick).unwrap_or(&Tick::new(0));
 
         if let Some(buffer) = buffers.get_mut(index) {
@@ -217,7 +270,7 @@ fn prepare_buffers<'a>(
         }
     }
 
-    Ok(&mut buffers[..acked_ticks.clients.len()])
+    Ok(&mut buffers[..clients_count])
 }
 
 /// Collect component changes into buffers based on last acknowledged tick.
@@ -231,6 +284,9 @@ fn collect_changes(
         buffer.start_array();
     }
 
+    let rooms = world.resource::<Rooms>();
+    let room_component_id = world.component_id::<RoomId>();
+    let visibility_component_id = world.component_id::<RoomVisibility>();
     for archetype in world
         .archetypes()
         .iter()
@@ -244,9 +300,29 @@ fn collect_changes(
             .get(archetype.table_id())
             .expect("archetype should be valid");
 
+        let has_room = room_component_id.is_some_and(|id| archetype.contains(id));
+        let has_visibility = visibility_component_id.is_some_and(|id| archetype.contains(id));
         for archetype_entity in archetype.entities() {
+            let entity = archetype_entity.entity();
+            let room_id = has_room
+                .then(|| world.get::<RoomId>(entity))
+                .flatten()
+                .copied();
+            let room_visibility = has_visibility
+                .then(|| world.get::<RoomVisibility>(entity))
+                .flatten();
             for buffer in &mut *buffers {
-                buffer.start_entity_data(archetype_entity.entity());
+                buffer.start_entity_data(entity);
+                buffer.entity_visibility = if !rooms.is_visible(room_id, buffer.client_id) {
+                    EntityVisibility::Hidden
+                } else if room_visibility
+                    .and_then(|visibility| visibility.gained.get(&buffer.client_id))
+                    .is_some_and(|tick| tick.is_newer_than(buffer.system_tick, system_tick))
+                {
+                    EntityVisibility::Gained
+                } else {
+                    EntityVisibility::Visible
+                };
             }
 
             for component_id in archetype.components() {
@@ -276,7 +352,7 @@ fn collect_changes(
                             unsafe { column.get_data_unchecked(archetype_entity.table_row()) };
 
                         for buffer in &mut *buffers {
-                            if ticks.is_changed(buffer.system_tick, system_tick) {
+                            if buffer.should_write(ticks, system_tick) {
                                 buffer.write_change(replication_info, replication_id, component)?;
                             }
                         }
@@ -288,7 +364,6 @@ fn collect_changes(
                             .get(component_id)
                             .unwrap_or_else(|| panic!("{component_id:?} should be in sparse set"));
 
-                        let entity = archetype_entity.entity();
                         let ticks = sparse_set
                             .get_ticks(entity)
                             .unwrap_or_else(|| panic!("{entity:?} should have {component_id:?}"));
@@ -297,7 +372,7 @@ fn collect_changes(
                             .unwrap_or_else(|| panic!("{entity:?} should have {component_id:?}"));
 
                         for buffer in &mut *buffers {
-                            if ticks.is_changed(buffer.system_tick, system_tick) {
+                            if buffer.should_write(ticks, system_tick) {
                                 buffer.write_change(replication_info, replication_id, component)?;
                             }
                         }
@@ -321,15 +396,19 @@ fn collect_changes(
 /// Collect component removals into buffers based on last acknowledged tick.
 fn collect_removals(
     buffers: &mut [ReplicationBuffer],
-    removal_trackers: &Query<(Entity, &RemovalTracker)>,
+    removal_trackers: &Query<(Entity, &RemovalTracker, Option<&RoomId>)>,
+    rooms: &Rooms,
     system_tick: Tick,
 ) -> Result<(), bincode::Error> {
     for buffer in &mut *buffers {
         buffer.start_array();
     }
 
-    for (entity, removal_tracker) in removal_trackers {
+    for (entity, removal_tracker, room_id) in removal_trackers {
         for buffer in &mut *buffers {
+            if !rooms.is_visible(room_id.copied(), buffer.client_id) {
+                continue;
+            }
             buffer.start_entity_data(entity);
             for (&replication_id, &tick) in &removal_tracker.0 {
                 if tick.is_newer_than(buffer.system_tick, system_tick) {
@@ -351,15 +430,31 @@ fn collect_removals(
 fn collect_despawns(
     buffers: &mut [ReplicationBuffer],
     despawn_tracker: &DespawnTracker,
+    room_visibilities: &Query<(Entity, &RoomVisibility)>,
     system_tick: Tick,
 ) -> Result<(), bincode::Error> {
     for buffer in &mut *buffers {
         buffer.start_array();
     }
 
-    for &(entity, tick) in &despawn_tracker.despawns {
+    for (entity, tick, receivers) in &despawn_tracker.despawns {
         for buffer in &mut *buffers {
-            if tick.is_newer_than(buffer.system_tick, system_tick) {
+            let visible = receivers
+                .as_ref()
+                .map_or(true, |receivers| receivers.contains(&buffer.client_id));
+            if visible && tick.is_newer_than(buffer.system_tick, system_tick) {
+                buffer.write_despawn(*entity)?;
+            }
+        }
+    }
+
+    for (entity, room_visibility) in room_visibilities {
+        for buffer in &mut *buffers {
+            if room_visibility
+                .lost
+                .get(&buffer.client_id)
+                .is_some_and(|tick| tick.is_newer_than(buffer.system_tick, system_tick))
+            {
                 buffer.write_despawn(entity)?;
             }
         }
@@ -388,6 +483,11 @@ pub enum ServerSet {
     ///
     /// Runs in `PostUpdate` on server tick, see [`TickPolicy`].
     Send,
+    /// Systems that send server events.
+    ///
+    /// Runs in `PostUpdate` every frame after [`ServerSet::Send`],
+    /// see [`SendTiming`](crate::network_event::SendTiming).
+    SendEvents,
 }
 
 pub enum TickPolicy {
@@ -397,6 +497,11 @@ pub enum TickPolicy {
     MaxTickRate(u16),
     /// Send updates from server every frame.
     EveryFrame,
+    /// Increment [`NetworkTick`] on each [`FixedUpdate`] run.
+    ///
+    /// Simulation steps and network ticks map 1:1. If multiple steps ran in a frame,
+    /// the update sent in [`ServerSet::Send`] will include changes from all of them.
+    FixedUpdate,
     /// [`ServerSet::Send`] should be manually configured.
     Manual,
 }
@@ -432,6 +537,12 @@ impl AckedTicks {
     }
 }
 
+/// Clients that received a replication message on the last server tick.
+///
+/// Updated in [`ServerSet::Send`].
+#[derive(Default, Resource)]
+pub(crate) struct ReplicatedClients(pub(crate) Vec<u64>);
+
 /// A reusable buffer with replicated data for a client.
 ///
 /// See also [Limits](../index.html#limits)
@@ -467,6 +578,9 @@ struct ReplicationBuffer {
 
     /// Entity from last call of [`Self::start_entity_data`].
     data_entity: Entity,
+
+    /// Visibility of [`Self::data_entity`] for the client.
+    entity_visibility: EntityVisibility,
 }
 
 impl ReplicationBuffer {
@@ -490,6 +604,7 @@ impl ReplicationBuffer {
             entity_data_pos: Default::default(),
             entity_data_len: Default::default(),
             data_entity: Entity::PLACEHOLDER,
+            entity_visibility: EntityVisibility::Visible,
         })
     }
 
@@ -601,13 +716,23 @@ impl ReplicationBuffer {
                 .array_len
                 .checked_add(1)
                 .ok_or(bincode::ErrorKind::SizeLimit)?;
-        } else {
-            self.message.set_position(self.entity_data_pos);
         }
 
         Ok(())
     }
 
+    /// Returns `true` if a component with `ticks` should be written for the current entity.
+    ///
+    /// Components are written if they changed since the last acknowledged tick
+    /// or if the client just started seeing the entity.
+    fn should_write(&self, ticks: ComponentTicks, system_tick: Tick) -> bool {
+        match self.entity_visibility {
+            EntityVisibility::Hidden => false,
+            EntityVisibility::Visible => ticks.is_changed(self.system_tick, system_tick),
+            EntityVisibility::Gained => true,
+        }
+    }
+
     /// Serializes `replication_id` and component from `ptr` into the buffer data.
     ///
     /// Should be called only inside entity data.
@@ -679,6 +804,19 @@ impl ReplicationBuffer {
     }
 }
 
+/// Visibility of an entity for a client.
+///
+/// Controlled by [`Rooms`].
+#[derive(Clone, Copy)]
+enum EntityVisibility {
+    /// The entity shouldn't be sent to the client.
+    Hidden,
+    /// The entity changes should be sent to the client.
+    Visible,
+    /// The client just started seeing the entity and all its components should be sent.
+    Gained,
+}
+
 /// Fills scene with all replicated entities and their components.
 ///
 /// # Panics
@@ -738,3 +876,32 @@ pub fn replicate_into_scene(scene: &mut DynamicScene, world: &World) {
         }
     }
 }
+
+/// Spawns entities from a scene created by [`replicate_into_scene`] and marks them for replication.
+///
+/// Entity references inside components that reflect [`MapEntities`](bevy::ecs::entity::MapEntities),
+/// such as [`ParentSync`](crate::parent_sync::ParentSync), are remapped to the spawned entities.
+/// Connected clients receive loaded entities as new spawns with the next tick.
+///
+/// Returns mapping from scene entities to the spawned entities.
+///
+/// # Errors
+///
+/// Returns an error if any component in the scene is not registered using `register_type()`
+/// or missing `#[reflect(Component)]`.
+pub fn load_replicated_scene(
+    scene: &DynamicScene,
+    world: &mut World,
+) -> Result<EntityMap, SceneSpawnError> {
+    // Make sure inserted components are newer than the last registered system tick
+    // even if no systems have run since the last replication.
+    world.increment_change_tick();
+
+    let mut entity_map = EntityMap::default();
+    scene.write_to_world(world, &mut entity_map)?;
+    for entity in entity_map.values() {
+        world.entity_mut(entity).insert(Replication);
+    }
+
+    Ok(entity_map)
+}
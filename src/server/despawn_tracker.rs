@@ -1,11 +1,14 @@
 use bevy::{
     ecs::{component::Tick, system::SystemChangeTick},
     prelude::*,
-    utils::HashSet,
+    utils::{HashMap, HashSet},
 };
 use bevy_renet::renet::RenetServer;
 
-use super::{AckedTicks, ServerSet};
+use super::{
+    rooms::{RoomVisibility, RoomsPlugin},
+    AckedTicks, ServerSet,
+};
 use crate::replicon_core::replication_rules::Replication;
 
 /// Tracks entity despawns of entities with [`Replication`] component in [`DespawnTracker`] resource.
@@ -19,9 +22,11 @@ impl Plugin for DespawnTrackerPlugin {
             PostUpdate,
             (
                 Self::entity_tracking_system,
+                Self::visibility_tracking_system.after(RoomsPlugin::visibility_system),
                 Self::cleanup_system,
                 Self::detection_system,
             )
+                .chain()
                 .before(ServerSet::Send)
                 .run_if(resource_exists::<RenetServer>()),
         );
@@ -34,7 +39,21 @@ impl DespawnTrackerPlugin {
         new_replicated_entities: Query<Entity, Added<Replication>>,
     ) {
         for entity in &new_replicated_entities {
-            tracker.tracked_entities.insert(entity);
+            tracker.tracked_entities.insert(entity, None);
+        }
+    }
+
+    /// Remembers clients that can see tracked entities with [`RoomVisibility`].
+    ///
+    /// Needed to send despawns only to these clients because the component is removed on despawn.
+    fn visibility_tracking_system(
+        mut tracker: ResMut<DespawnTracker>,
+        changed_visibilities: Query<(Entity, &RoomVisibility), Changed<RoomVisibility>>,
+    ) {
+        for (entity, visibility) in &changed_visibilities {
+            if let Some(receivers) = tracker.tracked_entities.get_mut(&entity) {
+                *receivers = Some(visibility.receivers().collect());
+            }
         }
     }
 
@@ -46,7 +65,7 @@ impl DespawnTrackerPlugin {
         mut despawn_tracker: ResMut<DespawnTracker>,
         acked_ticks: Res<AckedTicks>,
     ) {
-        despawn_tracker.despawns.retain(|(_, tick)| {
+        despawn_tracker.despawns.retain(|(_, tick, _)| {
             acked_ticks.clients.values().any(|acked_tick| {
                 let system_tick = *acked_ticks
                     .system_ticks
@@ -67,9 +86,9 @@ impl DespawnTrackerPlugin {
             ref mut despawns,
         } = *tracker;
 
-        tracked_entities.retain(|&entity| {
+        tracked_entities.retain(|&entity, receivers| {
             if entities.get(entity).is_err() {
-                despawns.push((entity, change_tick.this_run()));
+                despawns.push((entity, change_tick.this_run(), receivers.take()));
                 false
             } else {
                 true
@@ -80,9 +99,13 @@ impl DespawnTrackerPlugin {
 
 #[derive(Default, Resource)]
 pub(super) struct DespawnTracker {
-    tracked_entities: HashSet<Entity>,
-    /// Entities and ticks when they were despawned.
-    pub(super) despawns: Vec<(Entity, Tick)>,
+    /// Tracked entities and clients that can see them.
+    ///
+    /// [`None`] means that the entity is visible to all clients.
+    tracked_entities: HashMap<Entity, Option<HashSet<u64>>>,
+
+    /// Entities, ticks when they were despawned and clients that could see them.
+    pub(super) despawns: Vec<(Entity, Tick, Option<HashSet<u64>>)>,
 }
 
 #[cfg(test)]
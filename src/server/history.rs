@@ -0,0 +1,124 @@
+use std::{collections::VecDeque, marker::PhantomData};
+
+use bevy::prelude::*;
+
+use super::{has_authority, ServerSet};
+use crate::replicon_core::{replication_rules::Replication, NetworkTick};
+
+/// An extension trait for [`App`] for recording history of replicated components.
+pub trait ComponentHistoryAppExt {
+    /// Records values of component `C` of replicated entities into [`ComponentHistory<C>`] on each server tick.
+    ///
+    /// Values are kept for the last `ticks` ticks. Useful for lag compensation: server can rewind
+    /// hitboxes to the tick that the client was displaying (see [`ClientTick`](crate::network_event::tick_stamp::ClientTick)).
+    ///
+    /// Should be called on server.
+    fn record_history<C: Component + Clone>(&mut self, ticks: u32) -> &mut Self;
+}
+
+impl ComponentHistoryAppExt for App {
+    fn record_history<C: Component + Clone>(&mut self, ticks: u32) -> &mut Self {
+        self.insert_resource(HistoryLimit::<C>::new(ticks))
+            .add_systems(
+                PostUpdate,
+                (recording_system::<C>, cleanup_system::<C>)
+                    .in_set(ServerSet::SendEvents)
+                    .run_if(has_authority())
+                    .run_if(resource_changed::<NetworkTick>()),
+            )
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn recording_system<C: Component + Clone>(
+    mut commands: Commands,
+    network_tick: Res<NetworkTick>,
+    limit: Res<HistoryLimit<C>>,
+    mut entities: Query<(Entity, Ref<C>, Option<&mut ComponentHistory<C>>), With<Replication>>,
+) {
+    for (entity, component, history) in &mut entities {
+        match history {
+            Some(mut history) => {
+                if component.is_changed() {
+                    history.values.push_back((*network_tick, component.clone()));
+                }
+                history.trim(*network_tick, limit.ticks);
+            }
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(ComponentHistory::new(*network_tick, component.clone()));
+            }
+        }
+    }
+}
+
+fn cleanup_system<C: Component>(mut commands: Commands, mut removed: RemovedComponents<C>) {
+    for entity in &mut removed {
+        if let Some(mut entity) = commands.get_entity(entity) {
+            entity.remove::<ComponentHistory<C>>();
+        }
+    }
+}
+
+/// Number of ticks to keep in [`ComponentHistory<C>`].
+#[derive(Resource)]
+struct HistoryLimit<C> {
+    ticks: u32,
+    marker: PhantomData<C>,
+}
+
+impl<C> HistoryLimit<C> {
+    fn new(ticks: u32) -> Self {
+        Self {
+            ticks,
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Values of component `C` keyed by server ticks on which they were set.
+///
+/// Inserted automatically on server for replicated entities after calling
+/// [`ComponentHistoryAppExt::record_history`].
+#[derive(Component)]
+pub struct ComponentHistory<C> {
+    /// Values in order of their ticks. A value is stored only when it changes.
+    values: VecDeque<(NetworkTick, C)>,
+}
+
+impl<C> ComponentHistory<C> {
+    fn new(tick: NetworkTick, value: C) -> Self {
+        Self {
+            values: VecDeque::from([(tick, value)]),
+        }
+    }
+
+    /// Returns the value that the component had on the specified tick.
+    ///
+    /// Returns [`None`] if the tick is older than the recorded history.
+    pub fn get(&self, tick: NetworkTick) -> Option<&C> {
+        self.values
+            .iter()
+            .rev()
+            .find(|&&(value_tick, _)| value_tick <= tick)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns recorded values with their ticks from the oldest to the newest.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (NetworkTick, &C)> {
+        self.values.iter().map(|(tick, value)| (*tick, value))
+    }
+
+    /// Removes values that are no longer needed to answer [`Self::get`] for the last `ticks` ticks.
+    fn trim(&mut self, current_tick: NetworkTick, ticks: u32) {
+        let oldest_tick = NetworkTick::new(current_tick.get().wrapping_sub(ticks));
+        while self
+            .values
+            .get(1)
+            .is_some_and(|&(tick, _)| tick <= oldest_tick)
+        {
+            self.values.pop_front();
+        }
+    }
+}
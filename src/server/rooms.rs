@@ -0,0 +1,304 @@
+use bevy::{
+    ecs::{component::Tick, system::SystemChangeTick},
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+use bevy_renet::renet::{RenetServer, ServerEvent};
+use serde::{Deserialize, Serialize};
+
+use super::{AckedTicks, ServerSet};
+use crate::replicon_core::replication_rules::Replication;
+
+/// Tracks which clients can see entities with [`RoomId`] in [`RoomVisibility`] component.
+///
+/// Used only on server. Visibility changes will be cleaned after clients acknowledge them.
+pub(super) struct RoomsPlugin;
+
+impl Plugin for RoomsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rooms>()
+            .add_systems(
+                PreUpdate,
+                Self::disconnection_system
+                    .in_set(ServerSet::Receive)
+                    .run_if(resource_exists::<RenetServer>()),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    Self::insertion_system,
+                    apply_deferred,
+                    Self::visibility_system,
+                )
+                    .chain()
+                    .before(ServerSet::Send)
+                    .run_if(resource_exists::<RenetServer>()),
+            );
+    }
+}
+
+impl RoomsPlugin {
+    fn disconnection_system(mut server_events: EventReader<ServerEvent>, mut rooms: ResMut<Rooms>) {
+        for event in &mut server_events {
+            if let ServerEvent::ClientDisconnected { client_id, .. } = event {
+                rooms.remove_client_from_all(*client_id);
+            }
+        }
+    }
+
+    /// Inserts [`RoomVisibility`] for entities that were assigned to a room.
+    ///
+    /// New entities are visible only to room members. For already replicated entities
+    /// assumes that all connected clients could see them before, so clients outside
+    /// the room will receive despawn.
+    #[allow(clippy::type_complexity)]
+    fn insertion_system(
+        mut commands: Commands,
+        rooms: Res<Rooms>,
+        acked_ticks: Res<AckedTicks>,
+        new_room_entities: Query<
+            (Entity, &RoomId, Option<Ref<Replication>>),
+            (Added<RoomId>, Without<RoomVisibility>),
+        >,
+    ) {
+        for (entity, &room_id, replication) in &new_room_entities {
+            let replicated = replication.is_some_and(|replication| !replication.is_added());
+            let visible = acked_ticks
+                .clients
+                .keys()
+                .copied()
+                .filter(|&client_id| replicated || rooms.contains(room_id, client_id))
+                .collect();
+            commands.entity(entity).insert(RoomVisibility {
+                visible,
+                ..Default::default()
+            });
+        }
+    }
+
+    /// Updates visibility of entities based on [`Rooms`] and cleanups acknowledged visibility changes.
+    ///
+    /// [`RoomVisibility`] is marked as changed only when clients gain or lose visibility.
+    pub(super) fn visibility_system(
+        change_tick: SystemChangeTick,
+        rooms: Res<Rooms>,
+        acked_ticks: Res<AckedTicks>,
+        mut entities: Query<(Option<&RoomId>, &mut RoomVisibility)>,
+    ) {
+        for (room_id, mut visibility) in &mut entities {
+            let RoomVisibility {
+                ref mut visible,
+                ref mut gained,
+                ref mut lost,
+            } = *visibility.bypass_change_detection();
+
+            let mut changed = false;
+            for &client_id in acked_ticks.clients.keys() {
+                if rooms.is_visible(room_id.copied(), client_id) {
+                    if visible.insert(client_id) {
+                        gained.insert(client_id, change_tick.this_run());
+                        lost.remove(&client_id);
+                        changed = true;
+                    }
+                } else if visible.remove(&client_id) {
+                    lost.insert(client_id, change_tick.this_run());
+                    gained.remove(&client_id);
+                    changed = true;
+                }
+            }
+
+            visible.retain(|client_id| acked_ticks.clients.contains_key(client_id));
+            for changes in [gained, lost] {
+                changes.retain(|client_id, tick| {
+                    let Some(acked_tick) = acked_ticks.clients.get(client_id) else {
+                        return false;
+                    };
+                    let system_tick = *acked_ticks
+                        .system_ticks
+                        .get(acked_tick)
+                        .unwrap_or(&Tick::new(0));
+                    tick.is_newer_than(system_tick, change_tick.this_run())
+                });
+            }
+
+            if changed {
+                visibility.set_changed();
+            }
+        }
+    }
+}
+
+/// Identifier of a group of clients.
+///
+/// Entities with this component will be replicated only to clients in this room.
+/// Clients can be assigned to rooms using [`Rooms`].
+#[derive(Clone, Component, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct RoomId(pub u64);
+
+/// Stores room membership of clients.
+///
+/// Used only on server. Clients are removed from all rooms on disconnect.
+#[derive(Default, Resource)]
+pub struct Rooms {
+    clients: HashMap<u64, HashSet<RoomId>>,
+}
+
+impl Rooms {
+    /// Adds client to the room.
+    ///
+    /// A client can be in several rooms at the same time.
+    pub fn add_client(&mut self, room_id: RoomId, client_id: u64) {
+        self.clients.entry(client_id).or_default().insert(room_id);
+    }
+
+    /// Removes client from the room.
+    pub fn remove_client(&mut self, room_id: RoomId, client_id: u64) {
+        if let Some(rooms) = self.clients.get_mut(&client_id) {
+            rooms.remove(&room_id);
+            if rooms.is_empty() {
+                self.clients.remove(&client_id);
+            }
+        }
+    }
+
+    /// Removes client from all rooms.
+    pub fn remove_client_from_all(&mut self, client_id: u64) {
+        self.clients.remove(&client_id);
+    }
+
+    /// Returns `true` if the client is in the room.
+    pub fn contains(&self, room_id: RoomId, client_id: u64) -> bool {
+        self.clients
+            .get(&client_id)
+            .is_some_and(|rooms| rooms.contains(&room_id))
+    }
+
+    /// Returns `true` if an entity with `room_id` should be replicated to the client.
+    ///
+    /// Entities without room are visible for everyone.
+    pub(crate) fn is_visible(&self, room_id: Option<RoomId>, client_id: u64) -> bool {
+        room_id.map_or(true, |room_id| self.contains(room_id, client_id))
+    }
+
+    /// Returns rooms of the client.
+    pub fn client_rooms(&self, client_id: u64) -> impl Iterator<Item = RoomId> + '_ {
+        self.clients.get(&client_id).into_iter().flatten().copied()
+    }
+
+    /// Returns clients in the room.
+    pub fn room_clients(&self, room_id: RoomId) -> impl Iterator<Item = u64> + '_ {
+        self.clients
+            .iter()
+            .filter(move |(_, rooms)| rooms.contains(&room_id))
+            .map(|(&client_id, _)| client_id)
+    }
+}
+
+/// Visibility of an entity with [`RoomId`] for connected clients.
+#[derive(Component, Default)]
+pub(super) struct RoomVisibility {
+    /// Clients that currently see the entity.
+    visible: HashSet<u64>,
+
+    /// Clients and ticks when they started seeing the entity.
+    ///
+    /// All components should be sent to these clients until they acknowledge it.
+    pub(super) gained: HashMap<u64, Tick>,
+
+    /// Clients and ticks when they stopped seeing the entity.
+    ///
+    /// Despawn should be sent to these clients until they acknowledge it.
+    pub(super) lost: HashMap<u64, Tick>,
+}
+
+impl RoomVisibility {
+    /// Returns clients that see the entity or haven't acknowledged its despawn yet.
+    pub(super) fn receivers(&self) -> impl Iterator<Item = u64> + '_ {
+        self.visible.iter().chain(self.lost.keys()).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replicon_core::NetworkTick;
+
+    #[test]
+    fn visibility() {
+        let mut app = App::new();
+        app.add_plugins(RoomsPlugin)
+            .insert_resource(RenetServer::new(Default::default()))
+            .init_resource::<AckedTicks>()
+            .add_event::<ServerEvent>();
+
+        const MEMBER_ID: u64 = 1;
+        const OUTSIDER_ID: u64 = 2;
+        const ROOM_ID: RoomId = RoomId(0);
+        let mut acked_ticks = app.world.resource_mut::<AckedTicks>();
+        acked_ticks.clients.insert(MEMBER_ID, NetworkTick::new(0));
+        acked_ticks.clients.insert(OUTSIDER_ID, NetworkTick::new(0));
+        app.world
+            .resource_mut::<Rooms>()
+            .add_client(ROOM_ID, MEMBER_ID);
+
+        let entity = app.world.spawn(ROOM_ID).id();
+
+        app.update();
+        app.update();
+
+        let visibility = app.world.get::<RoomVisibility>(entity).unwrap();
+        assert!(visibility.visible.contains(&MEMBER_ID));
+        assert!(!visibility.visible.contains(&OUTSIDER_ID));
+        assert!(
+            !visibility.lost.contains_key(&OUTSIDER_ID),
+            "outsider never saw the new entity"
+        );
+
+        app.world
+            .resource_mut::<Rooms>()
+            .add_client(ROOM_ID, OUTSIDER_ID);
+
+        app.update();
+
+        let visibility = app.world.get::<RoomVisibility>(entity).unwrap();
+        assert!(visibility.visible.contains(&OUTSIDER_ID));
+        assert!(visibility.gained.contains_key(&OUTSIDER_ID));
+        assert!(!visibility.lost.contains_key(&OUTSIDER_ID));
+    }
+
+    #[test]
+    fn moving_to_room() {
+        let mut app = App::new();
+        app.add_plugins(RoomsPlugin)
+            .insert_resource(RenetServer::new(Default::default()))
+            .init_resource::<AckedTicks>()
+            .add_event::<ServerEvent>();
+
+        const MEMBER_ID: u64 = 1;
+        const OUTSIDER_ID: u64 = 2;
+        const ROOM_ID: RoomId = RoomId(0);
+        let mut acked_ticks = app.world.resource_mut::<AckedTicks>();
+        acked_ticks.clients.insert(MEMBER_ID, NetworkTick::new(0));
+        acked_ticks.clients.insert(OUTSIDER_ID, NetworkTick::new(0));
+        app.world
+            .resource_mut::<Rooms>()
+            .add_client(ROOM_ID, MEMBER_ID);
+
+        let entity = app.world.spawn(Replication).id();
+
+        app.update();
+
+        app.world.entity_mut(entity).insert(ROOM_ID);
+
+        app.update();
+        app.update();
+
+        let visibility = app.world.get::<RoomVisibility>(entity).unwrap();
+        assert!(visibility.visible.contains(&MEMBER_ID));
+        assert!(!visibility.visible.contains(&OUTSIDER_ID));
+        assert!(
+            visibility.lost.contains_key(&OUTSIDER_ID),
+            "outsider should receive despawn for already replicated entity"
+        );
+    }
+}
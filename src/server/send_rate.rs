@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_renet::renet::{RenetServer, ServerEvent};
+
+use super::ServerSet;
+use crate::replicon_core::NetworkTick;
+
+/// Decides on which ticks each client receives replication updates.
+///
+/// Used only on server. Skipped ticks are not lost: the next update for a client
+/// includes all changes since its last acknowledged tick.
+pub(super) struct SendRatePlugin;
+
+impl Plugin for SendRatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ClientSendRates>()
+            .add_systems(
+                PreUpdate,
+                Self::disconnection_system
+                    .in_set(ServerSet::Receive)
+                    .run_if(resource_exists::<RenetServer>()),
+            )
+            .add_systems(
+                PostUpdate,
+                Self::scheduling_system
+                    .before(ServerSet::Send)
+                    .run_if(resource_exists::<RenetServer>())
+                    .run_if(resource_changed::<NetworkTick>()),
+            );
+    }
+}
+
+impl SendRatePlugin {
+    fn disconnection_system(
+        mut server_events: EventReader<ServerEvent>,
+        mut send_rates: ResMut<ClientSendRates>,
+    ) {
+        for event in &mut server_events {
+            if let ServerEvent::ClientDisconnected { client_id, .. } = event {
+                send_rates.clients.remove(client_id);
+            }
+        }
+    }
+
+    /// Marks clients that should receive an update on this tick and adjusts their intervals
+    /// based on connection quality if [`AdaptiveSendRate`] is present.
+    fn scheduling_system(
+        mut send_rates: ResMut<ClientSendRates>,
+        server: Res<RenetServer>,
+        network_tick: Res<NetworkTick>,
+        adaptive: Option<Res<AdaptiveSendRate>>,
+    ) {
+        for client_id in server.clients_id_iter() {
+            let send_rate = send_rates.clients.entry(client_id).or_default();
+            send_rate.due = send_rate
+                .next_tick
+                .map_or(true, |next_tick| *network_tick >= next_tick);
+            if !send_rate.due {
+                continue;
+            }
+
+            if let Some(adaptive) = &adaptive {
+                let Ok(network_info) = server.network_info(client_id) else {
+                    continue;
+                };
+                let congested = network_info.rtt > adaptive.max_rtt.as_secs_f64()
+                    || network_info.packet_loss > adaptive.max_packet_loss
+                    || network_info.bytes_sent_per_second > adaptive.max_bytes_per_sec;
+
+                let previous_interval = send_rate.interval;
+                send_rate.interval = if congested {
+                    (send_rate.interval * 2).min(adaptive.max_interval.max(1))
+                } else {
+                    send_rate.interval.saturating_sub(1).max(1)
+                };
+                if send_rate.interval != previous_interval {
+                    debug!(
+                        "changed update interval for client {client_id} to {} ticks",
+                        send_rate.interval
+                    );
+                }
+            }
+
+            send_rate.next_tick = Some(NetworkTick::new(
+                network_tick.get().wrapping_add(send_rate.interval),
+            ));
+        }
+    }
+}
+
+/// Enables adaptive per-client send rate.
+///
+/// Insert this resource on server to send replication updates less often to clients with
+/// poor connection. When any of the limits is exceeded, the interval between updates
+/// for the client is doubled up to [`Self::max_interval`]. Otherwise it's decreased by one tick
+/// on each update until updates are sent on every tick again.
+///
+/// Current intervals are available in [`ClientSendRates`].
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct AdaptiveSendRate {
+    /// Maximum number of ticks between updates.
+    pub max_interval: u32,
+
+    /// Round-trip time above which the client is considered congested.
+    pub max_rtt: Duration,
+
+    /// Packet loss ratio in range `0.0..1.0` above which the client is considered congested.
+    pub max_packet_loss: f64,
+
+    /// Number of bytes sent to the client per second above which the client is considered congested.
+    pub max_bytes_per_sec: f64,
+}
+
+impl Default for AdaptiveSendRate {
+    fn default() -> Self {
+        Self {
+            max_interval: 8,
+            max_rtt: Duration::from_millis(250),
+            max_packet_loss: 0.1,
+            max_bytes_per_sec: 128.0 * 1024.0,
+        }
+    }
+}
+
+/// Replication update intervals for each client.
+///
+/// Used only on server. Without [`AdaptiveSendRate`] all clients receive updates on every tick.
+#[derive(Resource, Default)]
+pub struct ClientSendRates {
+    clients: HashMap<u64, ClientSendRate>,
+}
+
+impl ClientSendRates {
+    /// Returns the current number of ticks between updates for the client.
+    pub fn interval(&self, client_id: u64) -> u32 {
+        self.clients
+            .get(&client_id)
+            .map_or(1, |send_rate| send_rate.interval)
+    }
+
+    /// Returns iterator over client IDs and their current number of ticks between updates.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u32)> + '_ {
+        self.clients
+            .iter()
+            .map(|(&client_id, send_rate)| (client_id, send_rate.interval))
+    }
+
+    /// Returns `true` if the client should receive an update on the current tick.
+    ///
+    /// Valid inside [`ServerSet::Send`].
+    pub(crate) fn is_due(&self, client_id: u64) -> bool {
+        self.clients
+            .get(&client_id)
+            .map_or(true, |send_rate| send_rate.due)
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.clients.clear();
+    }
+}
+
+struct ClientSendRate {
+    /// Number of ticks between updates.
+    interval: u32,
+
+    /// Tick on which the next update should be sent.
+    next_tick: Option<NetworkTick>,
+
+    /// Whether an update should be sent on the current tick.
+    due: bool,
+}
+
+impl Default for ClientSendRate {
+    fn default() -> Self {
+        Self {
+            interval: 1,
+            next_tick: None,
+            due: true,
+        }
+    }
+}
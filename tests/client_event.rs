@@ -2,7 +2,9 @@ mod common;
 
 use bevy::prelude::*;
 use bevy::{ecs::event::Events, time::TimePlugin};
+use bevy_renet::renet::transport::NetcodeClientTransport;
 use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use common::{DummyEvent, ReflectEvent, ReflectEventDeserializer, ReflectEventSerializer};
 
@@ -86,6 +88,328 @@ fn mapping_and_sending_receiving() {
     assert_eq!(mapped_entities, [server_entity]);
 }
 
+#[test]
+fn unmapped_dropping() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((MinimalPlugins, ReplicationPlugins))
+            .add_mapped_client_event::<DummyEvent>(SendPolicy::Ordered);
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    client_app
+        .world
+        .resource_mut::<Events<DummyEvent>>()
+        .send(DummyEvent(Entity::from_raw(0)));
+
+    client_app.update();
+    server_app.update();
+
+    let client_events = server_app
+        .world
+        .resource::<Events<FromClient<DummyEvent>>>();
+    assert!(client_events.is_empty());
+
+    let diagnostics = client_app
+        .world
+        .resource::<EventMappingDiagnostics<DummyEvent>>();
+    assert_eq!(diagnostics.dropped, 1);
+}
+
+#[test]
+fn validation() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((MinimalPlugins, ReplicationPlugins))
+            .add_mapped_client_event::<DummyEvent>(SendPolicy::Ordered)
+            .validate_client_event::<DummyEvent>(EntityValidation::Owned);
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let client_id = client_app
+        .world
+        .resource::<NetcodeClientTransport>()
+        .client_id();
+    let not_replicated = server_app.world.spawn_empty().id();
+    let not_visible = server_app
+        .world
+        .spawn((Replication, RoomId(0), EntityOwner(client_id)))
+        .id();
+    let not_owned = server_app
+        .world
+        .spawn((Replication, EntityOwner(SERVER_ID)))
+        .id();
+    let owned = server_app
+        .world
+        .spawn((Replication, EntityOwner(client_id)))
+        .id();
+
+    for (server_entity, violation) in [
+        (
+            not_replicated,
+            Some(ViolationKind::NotReplicated(not_replicated)),
+        ),
+        (not_visible, Some(ViolationKind::NotVisible(not_visible))),
+        (not_owned, Some(ViolationKind::NotOwned(not_owned))),
+        (owned, None),
+    ] {
+        let client_entity = client_app.world.spawn_empty().id();
+        client_app
+            .world
+            .resource_mut::<NetworkEntityMap>()
+            .insert(server_entity, client_entity);
+
+        client_app
+            .world
+            .resource_mut::<Events<DummyEvent>>()
+            .send(DummyEvent(client_entity));
+
+        client_app.update();
+        server_app.update();
+
+        let client_events = server_app
+            .world
+            .resource_mut::<Events<FromClient<DummyEvent>>>()
+            .drain()
+            .count();
+        let violations: Vec<_> = server_app
+            .world
+            .resource_mut::<Events<ClientViolation>>()
+            .drain()
+            .map(|violation| violation.kind)
+            .collect();
+        assert_eq!(client_events, violation.is_none() as usize);
+        assert_eq!(violations, Vec::from_iter(violation));
+    }
+}
+
+#[test]
+fn rate_limiting() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((MinimalPlugins, ReplicationPlugins))
+            .add_client_event::<DummyEvent>(SendPolicy::Ordered)
+            .limit_client_event::<DummyEvent>(EventLimits {
+                messages_per_second: 0.0,
+                burst: 1,
+                ..Default::default()
+            });
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let mut dummy_events = client_app.world.resource_mut::<Events<DummyEvent>>();
+    dummy_events.send(DummyEvent(Entity::PLACEHOLDER));
+    dummy_events.send(DummyEvent(Entity::PLACEHOLDER));
+    dummy_events.send(DummyEvent(Entity::PLACEHOLDER));
+
+    client_app.update();
+    server_app.update();
+
+    let client_events = server_app
+        .world
+        .resource::<Events<FromClient<DummyEvent>>>();
+    assert_eq!(client_events.len(), 1);
+
+    let violations: Vec<_> = server_app
+        .world
+        .resource_mut::<Events<ClientViolation>>()
+        .drain()
+        .map(|violation| violation.kind)
+        .collect();
+    assert_eq!(violations, [ViolationKind::RateLimitExceeded { count: 2 }]);
+}
+
+#[test]
+fn throttling() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((MinimalPlugins, ReplicationPlugins))
+            .add_client_event::<DummyEvent>(SendPolicy::Ordered)
+            .limit_client_event::<DummyEvent>(EventLimits {
+                messages_per_second: 0.0,
+                burst: 1,
+                policy: LimitPolicy::Throttle,
+                ..Default::default()
+            });
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let mut dummy_events = client_app.world.resource_mut::<Events<DummyEvent>>();
+    dummy_events.send(DummyEvent(Entity::PLACEHOLDER));
+    dummy_events.send(DummyEvent(Entity::PLACEHOLDER));
+    dummy_events.send(DummyEvent(Entity::PLACEHOLDER));
+
+    client_app.update();
+    server_app.update();
+    server_app.update();
+
+    let client_events = server_app
+        .world
+        .resource::<Events<FromClient<DummyEvent>>>();
+    assert_eq!(client_events.len(), 1);
+
+    let violations = server_app.world.resource::<Events<ClientViolation>>();
+    assert_eq!(
+        violations.len(),
+        1,
+        "postponed message shouldn't be reported twice"
+    );
+}
+
+#[test]
+fn size_limiting() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((MinimalPlugins, ReplicationPlugins))
+            .add_client_event::<DummyEvent>(SendPolicy::Ordered)
+            .limit_client_event::<DummyEvent>(EventLimits {
+                max_message_size: 0,
+                policy: LimitPolicy::Disconnect,
+                ..Default::default()
+            });
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    client_app
+        .world
+        .resource_mut::<Events<DummyEvent>>()
+        .send(DummyEvent(Entity::PLACEHOLDER));
+
+    client_app.update();
+    server_app.update();
+
+    let client_events = server_app
+        .world
+        .resource::<Events<FromClient<DummyEvent>>>();
+    assert!(client_events.is_empty());
+
+    let violations: Vec<_> = server_app
+        .world
+        .resource_mut::<Events<ClientViolation>>()
+        .drain()
+        .map(|violation| violation.kind)
+        .collect();
+    assert!(matches!(
+        violations[..],
+        [ViolationKind::SizeLimitExceeded { count: 1, .. }]
+    ));
+
+    let client_id = client_app
+        .world
+        .resource::<NetcodeClientTransport>()
+        .client_id();
+    let server = server_app.world.resource::<RenetServer>();
+    assert!(!server.is_connected(client_id));
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn compressed_size_limiting() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((MinimalPlugins, ReplicationPlugins))
+            .insert_resource(NetworkCompression {
+                algorithm: CompressionAlgorithm::Lz4,
+                threshold: 0,
+                ..Default::default()
+            })
+            .add_client_event::<BulkyEvent>(SendPolicy::Ordered)
+            .compress_event::<BulkyEvent>()
+            .limit_client_event::<BulkyEvent>(EventLimits {
+                max_message_size: 256,
+                ..Default::default()
+            });
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    client_app
+        .world
+        .resource_mut::<Events<BulkyEvent>>()
+        .send(BulkyEvent(vec![0; 1024]));
+
+    client_app.update();
+    server_app.update();
+
+    let client_events = server_app
+        .world
+        .resource::<Events<FromClient<BulkyEvent>>>();
+    assert!(
+        client_events.is_empty(),
+        "limit should apply to the decompressed size"
+    );
+
+    let violations: Vec<_> = server_app
+        .world
+        .resource_mut::<Events<ClientViolation>>()
+        .drain()
+        .map(|violation| violation.kind)
+        .collect();
+    assert!(matches!(
+        violations[..],
+        [ViolationKind::SizeLimitExceeded { size, .. }] if size > 1024
+    ));
+}
+
+#[test]
+fn tick_stamping() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .replicate::<DummyComponent>()
+        .add_client_event::<DummyEvent>(SendPolicy::Ordered)
+        .stamp_client_event::<DummyEvent>();
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    // Replicate an entity to update the client tick.
+    server_app.world.spawn((Replication, DummyComponent));
+    server_app.update();
+    client_app.update();
+
+    let last_tick = **client_app.world.resource::<LastTick>();
+    assert_ne!(last_tick, NetworkTick::default());
+
+    *client_app.world.resource_mut::<InterpolationOffset>() = InterpolationOffset(0.5);
+    client_app
+        .world
+        .resource_mut::<Events<DummyEvent>>()
+        .send(DummyEvent(Entity::PLACEHOLDER));
+
+    client_app.update();
+    server_app.update();
+
+    let ticks: Vec<_> = server_app
+        .world
+        .resource_mut::<Events<FromClient<DummyEvent>>>()
+        .drain()
+        .map(|event| event.tick)
+        .collect();
+    assert_eq!(
+        ticks,
+        [Some(ClientTick {
+            tick: last_tick,
+            interpolation_offset: 0.5,
+        })]
+    );
+}
+
 #[test]
 fn sending_receiving_reflect() {
     let mut server_app = App::new();
@@ -117,6 +441,34 @@ fn sending_receiving_reflect() {
     assert_eq!(client_events.len(), 1);
 }
 
+#[test]
+fn sending_receiving_reflect_serde() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((MinimalPlugins, ReplicationPlugins))
+            .register_type::<ReflectedValue>()
+            .add_client_reflect_event::<BoxedEvent, ReflectEventSerde<_>, ReflectEventSerde<_>>(
+                SendPolicy::Ordered,
+            );
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    client_app
+        .world
+        .resource_mut::<Events<BoxedEvent>>()
+        .send(BoxedEvent(ReflectedValue.clone_value()));
+
+    client_app.update();
+    server_app.update();
+
+    let client_events = server_app
+        .world
+        .resource::<Events<FromClient<BoxedEvent>>>();
+    assert_eq!(client_events.len(), 1);
+}
+
 #[test]
 fn mapping_and_sending_receiving_reflect() {
     let mut server_app = App::new();
@@ -174,3 +526,23 @@ fn local_resending() {
     let client_events = app.world.resource::<Events<FromClient<DummyEvent>>>();
     assert_eq!(client_events.len(), 1);
 }
+
+#[derive(Debug, Event)]
+struct BoxedEvent(Box<dyn Reflect>);
+
+impl ReflectedEvent for BoxedEvent {
+    fn as_reflect(&self) -> &dyn Reflect {
+        &*self.0
+    }
+
+    fn from_reflect(reflect: Box<dyn Reflect>) -> Option<Self> {
+        Some(Self(reflect))
+    }
+}
+
+#[derive(Component, Deserialize, Serialize)]
+struct DummyComponent;
+
+#[cfg(feature = "lz4")]
+#[derive(Debug, Deserialize, Event, Serialize)]
+struct BulkyEvent(Vec<u8>);
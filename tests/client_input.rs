@@ -0,0 +1,218 @@
+mod common;
+
+use bevy::{ecs::event::Events, prelude::*};
+use bevy_renet::renet::RenetClient;
+use bevy_replicon::{network_event::EventChannel, prelude::*};
+use bincode::{DefaultOptions, Options};
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn sending_receiving() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_client_input::<DummyInput>(InputSettings {
+            buffer_delay: 1,
+            ..Default::default()
+        });
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    client_app.world.send_event(DummyInput(0));
+
+    client_app.update();
+    server_app.update();
+
+    let inputs: Vec<_> = server_app
+        .world
+        .resource_mut::<Events<ClientInput<DummyInput>>>()
+        .drain()
+        .map(|input| (input.sequence, input.input))
+        .collect();
+    assert_eq!(inputs, [(0, DummyInput(0))]);
+}
+
+#[test]
+fn releasing_one_per_tick() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_client_input::<DummyInput>(InputSettings {
+            buffer_delay: 2,
+            ..Default::default()
+        });
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    for value in 0..2 {
+        client_app.world.send_event(DummyInput(value));
+        client_app.update();
+    }
+
+    for value in 0..2 {
+        server_app.update();
+
+        let inputs: Vec<_> = server_app
+            .world
+            .resource_mut::<Events<ClientInput<DummyInput>>>()
+            .drain()
+            .map(|input| input.input)
+            .collect();
+        assert_eq!(inputs, [DummyInput(value)]);
+    }
+}
+
+#[test]
+fn repeating_last() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_client_input::<DummyInput>(InputSettings {
+            buffer_delay: 1,
+            missing: MissingInput::RepeatLast,
+            ..Default::default()
+        });
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    client_app.world.send_event(DummyInput(0));
+
+    client_app.update();
+    server_app.update();
+    server_app
+        .world
+        .resource_mut::<Events<ClientInput<DummyInput>>>()
+        .clear();
+
+    server_app.update();
+
+    let inputs: Vec<_> = server_app
+        .world
+        .resource_mut::<Events<ClientInput<DummyInput>>>()
+        .drain()
+        .map(|input| (input.input, input.repeated))
+        .collect();
+    assert_eq!(inputs, [(DummyInput(0), true)]);
+}
+
+#[test]
+fn overflowing_sequence() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_client_input::<DummyInput>(InputSettings {
+            buffer_delay: 1,
+            ..Default::default()
+        });
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let message = DefaultOptions::new()
+        .serialize(&(
+            u64::MAX,
+            vec![
+                (ClientTick::default(), DummyInput(0)),
+                (ClientTick::default(), DummyInput(1)),
+            ],
+        ))
+        .unwrap();
+    let channel_id = client_app.world.resource::<EventChannel<DummyInput>>().id;
+    client_app
+        .world
+        .resource_mut::<RenetClient>()
+        .send_message(channel_id, message);
+
+    client_app.update();
+    server_app.update();
+
+    let inputs: Vec<_> = server_app
+        .world
+        .resource_mut::<Events<ClientInput<DummyInput>>>()
+        .drain()
+        .map(|input| (input.sequence, input.input))
+        .collect();
+    assert_eq!(inputs, [(u64::MAX, DummyInput(0))]);
+}
+
+#[test]
+fn tick_stamping() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .replicate::<DummyComponent>()
+        .add_client_input::<DummyInput>(InputSettings {
+            buffer_delay: 1,
+            ..Default::default()
+        });
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    // Replicate an entity to update the client tick.
+    server_app.world.spawn((Replication, DummyComponent));
+    server_app.update();
+    client_app.update();
+
+    let last_tick = **client_app.world.resource::<LastTick>();
+    assert_ne!(last_tick, NetworkTick::default());
+
+    client_app.world.send_event(DummyInput(0));
+
+    client_app.update();
+    server_app.update();
+
+    let ticks: Vec<_> = server_app
+        .world
+        .resource_mut::<Events<ClientInput<DummyInput>>>()
+        .drain()
+        .map(|input| input.tick.tick)
+        .collect();
+    assert_eq!(ticks, [last_tick]);
+}
+
+#[test]
+fn local_resending() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+    ))
+    .add_client_input::<DummyInput>(Default::default());
+
+    app.world.send_event(DummyInput(0));
+
+    app.update();
+
+    let inputs = app.world.resource::<Events<ClientInput<DummyInput>>>();
+    assert_eq!(inputs.len(), 1);
+}
+
+#[derive(Clone, Debug, Deserialize, Event, PartialEq, Serialize)]
+struct DummyInput(u32);
+
+#[derive(Component, Deserialize, Serialize)]
+struct DummyComponent;
@@ -0,0 +1,72 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+
+#[test]
+fn synchronization() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins
+                .set(ServerPlugin::new(TickPolicy::EveryFrame))
+                .set(ClockSyncPlugin::new(Duration::ZERO)),
+        ));
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    for _ in 0..5 {
+        thread::sleep(Duration::from_millis(10));
+        client_app.update();
+        server_app.update();
+    }
+    client_app.update();
+
+    let server_time = *client_app.world.resource::<ServerTime>();
+    assert!(server_time.is_synced());
+    assert!(server_time.rtt() < Duration::from_millis(500));
+    assert!(server_time.tick_rate().is_some());
+
+    let server_elapsed = server_app
+        .world
+        .resource::<Time>()
+        .raw_elapsed_seconds_f64();
+    assert!(
+        (server_time.elapsed_seconds() - server_elapsed).abs() < 0.5,
+        "estimated server time {} should be close to {server_elapsed}",
+        server_time.elapsed_seconds()
+    );
+
+    let network_tick = server_app.world.resource::<NetworkTick>().get();
+    assert!(
+        server_time.tick().get().abs_diff(network_tick) <= 2,
+        "estimated server tick {:?} should be close to {network_tick}",
+        server_time.tick()
+    );
+}
+
+#[test]
+fn local_time() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+    ));
+
+    app.update();
+    app.update();
+
+    let server_time = app.world.resource::<ServerTime>();
+    assert!(server_time.is_synced());
+    assert_eq!(server_time.rtt(), Duration::ZERO);
+    assert_eq!(
+        server_time.tick(),
+        NetworkTick::new(1),
+        "tick should match local tick at the beginning of the frame"
+    );
+}
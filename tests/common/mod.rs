@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use std::{
     any,
     fmt::{self, Formatter},
@@ -28,39 +30,54 @@ use serde::{
 use strum::{EnumVariantNames, IntoStaticStr, VariantNames};
 
 pub(super) fn connect(server_app: &mut App, client_app: &mut App) {
+    connect_multiple(server_app, &mut [client_app]);
+}
+
+pub(super) fn connect_multiple(server_app: &mut App, client_apps: &mut [&mut App]) {
     let server_channels = server_app
         .world
         .resource_mut::<NetworkChannels>()
         .server_channels();
-    let client_channels = client_app
+    let client_channels = server_app
         .world
         .resource_mut::<NetworkChannels>()
         .client_channels();
 
-    let (server, server_transport) =
-        create_server(server_channels.clone(), client_channels.clone());
-    let (client, client_transport) = create_client(
-        server_transport.addr().port(),
-        server_channels,
-        client_channels,
+    let (server, server_transport) = create_server(
+        client_apps.len(),
+        server_channels.clone(),
+        client_channels.clone(),
     );
+    let port = server_transport.addr().port();
 
     server_app
         .insert_resource(server)
         .insert_resource(server_transport);
 
-    client_app
-        .insert_resource(client)
-        .insert_resource(client_transport);
+    for (index, client_app) in client_apps.iter_mut().enumerate() {
+        let (client, client_transport) = create_client(
+            index as u64,
+            port,
+            server_channels.clone(),
+            client_channels.clone(),
+        );
+
+        client_app
+            .insert_resource(client)
+            .insert_resource(client_transport);
+    }
 
     loop {
-        client_app.update();
+        for client_app in client_apps.iter_mut() {
+            client_app.update();
+        }
         server_app.update();
-        if client_app
-            .world
-            .resource::<NetcodeClientTransport>()
-            .is_connected()
-        {
+        if client_apps.iter().all(|client_app| {
+            client_app
+                .world
+                .resource::<NetcodeClientTransport>()
+                .is_connected()
+        }) {
             break;
         }
     }
@@ -69,6 +86,7 @@ pub(super) fn connect(server_app: &mut App, client_app: &mut App) {
 const PROTOCOL_ID: u64 = 0;
 
 fn create_server(
+    max_clients: usize,
     server_channels_config: Vec<ChannelConfig>,
     client_channels_config: Vec<ChannelConfig>,
 ) -> (RenetServer, NetcodeServerTransport) {
@@ -87,7 +105,7 @@ fn create_server(
         .local_addr()
         .expect("socket should autodetect local address");
     let server_config = ServerConfig {
-        max_clients: 1,
+        max_clients,
         protocol_id: PROTOCOL_ID,
         public_addr,
         authentication: ServerAuthentication::Unsecure,
@@ -99,6 +117,7 @@ fn create_server(
 }
 
 fn create_client(
+    index: u64,
     port: u16,
     server_channels_config: Vec<ChannelConfig>,
     client_channels_config: Vec<ChannelConfig>,
@@ -112,7 +131,7 @@ fn create_client(
     let current_time = SystemTime::now()
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap();
-    let client_id = current_time.as_millis() as u64;
+    let client_id = current_time.as_millis() as u64 + index;
     let ip = Ipv4Addr::LOCALHOST.into();
     let server_addr = SocketAddr::new(ip, port);
     let socket = UdpSocket::bind((ip, 0)).expect("localhost should be bindable");
@@ -0,0 +1,72 @@
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn recording() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+    ))
+    .replicate::<DummyComponent>()
+    .record_history::<DummyComponent>(1);
+
+    let entity = app.world.spawn((Replication, DummyComponent(0))).id();
+
+    app.update();
+    let first_tick = *app.world.resource::<NetworkTick>();
+
+    app.world.get_mut::<DummyComponent>(entity).unwrap().0 = 1;
+
+    app.update();
+    let second_tick = *app.world.resource::<NetworkTick>();
+
+    let history = app
+        .world
+        .get::<ComponentHistory<DummyComponent>>(entity)
+        .unwrap();
+    assert_eq!(history.get(first_tick), Some(&DummyComponent(0)));
+    assert_eq!(history.get(second_tick), Some(&DummyComponent(1)));
+
+    app.update();
+    let third_tick = *app.world.resource::<NetworkTick>();
+
+    let history = app
+        .world
+        .get::<ComponentHistory<DummyComponent>>(entity)
+        .unwrap();
+    assert_eq!(
+        history.get(first_tick),
+        None,
+        "tick should be outside of the limit"
+    );
+    assert_eq!(history.get(third_tick), Some(&DummyComponent(1)));
+}
+
+#[test]
+fn removal() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+    ))
+    .replicate::<DummyComponent>()
+    .record_history::<DummyComponent>(1);
+
+    let entity = app.world.spawn((Replication, DummyComponent(0))).id();
+
+    app.update();
+
+    app.world.entity_mut(entity).remove::<DummyComponent>();
+
+    app.update();
+
+    assert!(!app
+        .world
+        .entity(entity)
+        .contains::<ComponentHistory<DummyComponent>>());
+}
+
+#[derive(Clone, Component, Debug, Deserialize, PartialEq, Serialize)]
+struct DummyComponent(u32);
@@ -0,0 +1,192 @@
+mod common;
+
+use bevy::{ecs::event::Events, prelude::*};
+use bevy_replicon::{desync_detection::ReceivedChecksums, prelude::*};
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn matching_state() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+            DesyncDetectionPlugin::default(),
+        ))
+        .replicate::<DummyComponent>()
+        .checksum_component::<DummyComponent>();
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app.world.spawn((Replication, DummyComponent(0)));
+
+    server_app.update();
+    client_app.update();
+
+    let desync_events = client_app.world.resource::<Events<DesyncDetected>>();
+    assert!(desync_events.is_empty());
+
+    let last_tick = **client_app.world.resource::<LastTick>();
+    let received_checksums = client_app.world.resource::<ReceivedChecksums>();
+    assert_eq!(received_checksums.last_verified(), Some(last_tick));
+}
+
+#[test]
+fn idle_world() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+            DesyncDetectionPlugin::default(),
+        ))
+        .replicate::<DummyComponent>()
+        .checksum_component::<DummyComponent>();
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app.world.spawn((Replication, DummyComponent(0)));
+
+    for _ in 0..100 {
+        server_app.update();
+        client_app.update();
+    }
+
+    let received_checksums = client_app.world.resource::<ReceivedChecksums>();
+    assert!(
+        received_checksums.is_empty(),
+        "checksums shouldn't be sent without updates"
+    );
+    assert!(received_checksums.last_verified().is_some());
+}
+
+#[test]
+fn ignored_component() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+            DesyncDetectionPlugin::default(),
+        ))
+        .replicate::<DummyComponent>()
+        .checksum_component::<DummyComponent>();
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app.world.spawn((
+        Replication,
+        DummyComponent(0),
+        Ignored::<DummyComponent>::default(),
+    ));
+
+    server_app.update();
+    client_app.update();
+
+    let desync_events = client_app.world.resource::<Events<DesyncDetected>>();
+    assert!(
+        desync_events.is_empty(),
+        "ignored components shouldn't be included into checksums"
+    );
+}
+
+#[test]
+fn client_mutation() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+            DesyncDetectionPlugin::new(true),
+        ))
+        .replicate::<DummyComponent>()
+        .checksum_component::<DummyComponent>();
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let server_entity = server_app
+        .world
+        .spawn((Replication, DummyComponent(0)))
+        .id();
+    let other_server_entity = server_app
+        .world
+        .spawn((Replication, DummyComponent(0)))
+        .id();
+
+    server_app.update();
+    client_app.update();
+
+    let entity_map = client_app.world.resource::<NetworkEntityMap>();
+    let client_entity = entity_map.to_client()[&server_entity];
+
+    // Mutate replicated state on client and trigger an update for another entity.
+    client_app
+        .world
+        .get_mut::<DummyComponent>(client_entity)
+        .unwrap()
+        .0 = 1;
+    server_app
+        .world
+        .get_mut::<DummyComponent>(other_server_entity)
+        .unwrap()
+        .0 = 1;
+
+    server_app.update();
+    client_app.update();
+
+    let desync_events: Vec<_> = client_app
+        .world
+        .resource_mut::<Events<DesyncDetected>>()
+        .drain()
+        .collect();
+    assert_eq!(desync_events.len(), 1);
+    assert_eq!(desync_events[0].entities, [client_entity]);
+}
+
+#[test]
+fn mapped_component() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+            DesyncDetectionPlugin::default(),
+        ))
+        .replicate_mapped::<MappedComponent>()
+        .checksum_mapped_component::<MappedComponent>();
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let server_entity = server_app.world.spawn(Replication).id();
+    server_app
+        .world
+        .spawn((Replication, MappedComponent(server_entity)));
+
+    server_app.update();
+    client_app.update();
+
+    let desync_events = client_app.world.resource::<Events<DesyncDetected>>();
+    assert!(desync_events.is_empty());
+}
+
+#[derive(Component, Deserialize, Serialize)]
+struct DummyComponent(u32);
+
+#[derive(Clone, Component, Deserialize, Serialize)]
+struct MappedComponent(Entity);
+
+impl MapNetworkEntities for MappedComponent {
+    fn map_entities<T: Mapper>(&mut self, mapper: &mut T) {
+        self.0 = mapper.map(self.0);
+    }
+}
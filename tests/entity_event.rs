@@ -0,0 +1,155 @@
+mod common;
+
+use bevy::{ecs::event::Events, prelude::*, time::TimePlugin};
+use bevy_renet::renet::transport::NetcodeClientTransport;
+use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn sending_receiving() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .replicate::<DummyComponent>()
+        .add_server_entity_event::<DummyEvent>(SendPolicy::Ordered);
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    // Send event in the same frame as the spawn.
+    let server_entity = server_app.world.spawn((DummyComponent, Replication)).id();
+    server_app
+        .world
+        .resource_mut::<Events<ToEntity<DummyEvent>>>()
+        .send(ToEntity {
+            entity: server_entity,
+            event: DummyEvent,
+        });
+
+    server_app.update();
+    client_app.update();
+
+    let client_entity = *client_app
+        .world
+        .resource::<NetworkEntityMap>()
+        .to_client()
+        .get(&server_entity)
+        .expect("entity should be replicated");
+    let entities: Vec<_> = client_app
+        .world
+        .resource_mut::<Events<EntityEvent<DummyEvent>>>()
+        .drain()
+        .map(|event| event.entity)
+        .collect();
+    assert_eq!(entities, [client_entity]);
+}
+
+#[test]
+fn room_filtering() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .replicate::<DummyComponent>()
+        .add_server_entity_event::<DummyEvent>(SendPolicy::Ordered);
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    const ROOM_ID: RoomId = RoomId(0);
+    let server_entity = server_app
+        .world
+        .spawn((DummyComponent, Replication, ROOM_ID))
+        .id();
+
+    server_app.update();
+    client_app.update();
+
+    server_app
+        .world
+        .resource_mut::<Events<ToEntity<DummyEvent>>>()
+        .send(ToEntity {
+            entity: server_entity,
+            event: DummyEvent,
+        });
+
+    server_app.update();
+    client_app.update();
+
+    let entity_events = client_app
+        .world
+        .resource::<Events<EntityEvent<DummyEvent>>>();
+    assert!(
+        entity_events.is_empty(),
+        "event shouldn't be sent for entity outside of client rooms"
+    );
+
+    let client_id = client_app
+        .world
+        .resource::<NetcodeClientTransport>()
+        .client_id();
+    server_app
+        .world
+        .resource_mut::<Rooms>()
+        .add_client(ROOM_ID, client_id);
+
+    server_app
+        .world
+        .resource_mut::<Events<ToEntity<DummyEvent>>>()
+        .send(ToEntity {
+            entity: server_entity,
+            event: DummyEvent,
+        });
+
+    server_app.update();
+    client_app.update();
+
+    let entity_events = client_app
+        .world
+        .resource::<Events<EntityEvent<DummyEvent>>>();
+    assert_eq!(entity_events.len(), 1);
+}
+
+#[test]
+fn local_resending() {
+    let mut app = App::new();
+    app.add_plugins((
+        TimePlugin,
+        ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+    ))
+    .add_server_entity_event::<DummyEvent>(SendPolicy::Ordered);
+
+    let replicated_entity = app.world.spawn(Replication).id();
+    let entity = app.world.spawn_empty().id();
+    for entity in [replicated_entity, entity] {
+        app.world
+            .resource_mut::<Events<ToEntity<DummyEvent>>>()
+            .send(ToEntity {
+                entity,
+                event: DummyEvent,
+            });
+    }
+
+    app.update();
+
+    let entities: Vec<_> = app
+        .world
+        .resource_mut::<Events<EntityEvent<DummyEvent>>>()
+        .drain()
+        .map(|event| event.entity)
+        .collect();
+    assert_eq!(entities, [replicated_entity]);
+}
+
+#[derive(Component, Deserialize, Serialize)]
+struct DummyComponent;
+
+#[derive(Debug, Deserialize, Event, Serialize)]
+struct DummyEvent;
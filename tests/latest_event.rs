@@ -0,0 +1,121 @@
+mod common;
+
+use bevy::{ecs::event::Events, prelude::*};
+use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn superseding_by_type() {
+    let values = send(Superseding::ByType, [(0, 0), (1, 1), (0, 2)]);
+    assert_eq!(values, [2]);
+}
+
+#[test]
+fn superseding_by_key() {
+    let values = send(
+        Superseding::ByKey(|event: &DummyEvent| event.key),
+        [(0, 0), (1, 1), (0, 2)],
+    );
+    assert_eq!(values.len(), 2);
+    assert!(values.contains(&1));
+    assert!(values.contains(&2));
+}
+
+#[test]
+fn resending_until_acked() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_latest_server_event::<DummyEvent>(Superseding::ByType);
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app
+        .world
+        .resource_mut::<Events<ToClients<DummyEvent>>>()
+        .send(ToClients {
+            mode: SendMode::Broadcast,
+            event: DummyEvent { key: 0, value: 0 },
+        });
+
+    // Send the event twice since client didn't acknowledge it yet.
+    server_app.update();
+    server_app.update();
+    client_app.update();
+
+    let dummy_events = client_app.world.resource::<Events<DummyEvent>>();
+    assert_eq!(dummy_events.len(), 1, "resent event should be ignored");
+
+    // Receive the acknowledgment.
+    server_app.update();
+    client_app.update();
+    server_app.update();
+    client_app.update();
+
+    let dummy_events = client_app.world.resource::<Events<DummyEvent>>();
+    assert!(dummy_events.is_empty());
+}
+
+#[test]
+fn local_resending() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+    ))
+    .add_latest_server_event::<DummyEvent>(Superseding::ByType);
+
+    app.world
+        .resource_mut::<Events<ToClients<DummyEvent>>>()
+        .send(ToClients {
+            mode: SendMode::Broadcast,
+            event: DummyEvent { key: 0, value: 0 },
+        });
+
+    app.update();
+
+    let dummy_events = app.world.resource::<Events<DummyEvent>>();
+    assert_eq!(dummy_events.len(), 1);
+}
+
+/// Sends events with the specified keys and values in a single frame and returns received values.
+fn send<const N: usize>(superseding: Superseding<DummyEvent>, events: [(u64, u32); N]) -> Vec<u32> {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_latest_server_event::<DummyEvent>(superseding);
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let mut server_events = server_app
+        .world
+        .resource_mut::<Events<ToClients<DummyEvent>>>();
+    for (key, value) in events {
+        server_events.send(ToClients {
+            mode: SendMode::Broadcast,
+            event: DummyEvent { key, value },
+        });
+    }
+
+    server_app.update();
+    client_app.update();
+
+    let mut dummy_events = client_app.world.resource_mut::<Events<DummyEvent>>();
+    dummy_events.drain().map(|event| event.value).collect()
+}
+
+#[derive(Deserialize, Event, Serialize, Debug)]
+struct DummyEvent {
+    key: u64,
+    value: u32,
+}
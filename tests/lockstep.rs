@@ -0,0 +1,184 @@
+mod common;
+
+use bevy::{ecs::event::Events, prelude::*};
+use bevy_renet::renet::{transport::NetcodeClientTransport, RenetClient};
+use bevy_replicon::{network_event::EventChannel, prelude::*};
+use bincode::{DefaultOptions, Options};
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn stepping() {
+    let mut server_app = App::new();
+    let mut client_app1 = App::new();
+    let mut client_app2 = App::new();
+    for app in [&mut server_app, &mut client_app1, &mut client_app2] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_lockstep_commands::<DummyCommand>(1);
+    }
+
+    common::connect_multiple(&mut server_app, &mut [&mut client_app1, &mut client_app2]);
+
+    let client_id = client_app1
+        .world
+        .resource::<NetcodeClientTransport>()
+        .client_id();
+    client_app1.world.send_event(DummyCommand(1));
+
+    let mut steps = [Vec::new(), Vec::new(), Vec::new()];
+    for _ in 0..5 {
+        client_app1.update();
+        client_app2.update();
+        server_app.update();
+
+        for (app, steps) in [&mut server_app, &mut client_app1, &mut client_app2]
+            .into_iter()
+            .zip(&mut steps)
+        {
+            steps.extend(drain_steps(app));
+        }
+    }
+    client_app1.update();
+    client_app2.update();
+    steps[1].extend(drain_steps(&mut client_app1));
+    steps[2].extend(drain_steps(&mut client_app2));
+
+    let [server_steps, client_steps1, client_steps2] = steps;
+    assert!(!server_steps.is_empty());
+    assert!(
+        client_steps1.ends_with(&server_steps),
+        "client should execute the same steps as server"
+    );
+    assert!(
+        client_steps2.ends_with(&server_steps),
+        "client should execute the same steps as server"
+    );
+
+    let commands: Vec<_> = server_steps
+        .into_iter()
+        .flat_map(|(_, commands)| commands)
+        .collect();
+    assert_eq!(commands, [(client_id, 1)]);
+}
+
+#[test]
+fn waiting_for_clients() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_lockstep_commands::<DummyCommand>(2);
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    for _ in 0..5 {
+        server_app.update();
+    }
+    server_app
+        .world
+        .resource_mut::<Events<LockstepStep<DummyCommand>>>()
+        .clear();
+
+    server_app.update();
+
+    let step_events = server_app
+        .world
+        .resource::<Events<LockstepStep<DummyCommand>>>();
+    assert!(
+        step_events.is_empty(),
+        "server should wait for client commands"
+    );
+
+    client_app.update();
+    server_app.update();
+
+    let step_events = server_app
+        .world
+        .resource::<Events<LockstepStep<DummyCommand>>>();
+    assert_eq!(step_events.len(), 1);
+}
+
+#[test]
+fn far_ahead_rejection() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_lockstep_commands::<DummyCommand>(1);
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let tick = NetworkTick::new(100);
+    let message = DefaultOptions::new()
+        .serialize(&(tick, vec![DummyCommand(1)]))
+        .unwrap();
+    let channel_id = client_app.world.resource::<EventChannel<DummyCommand>>().id;
+    client_app
+        .world
+        .resource_mut::<RenetClient>()
+        .send_message(channel_id, message);
+
+    client_app.update();
+    server_app.update();
+
+    let violations: Vec<_> = server_app
+        .world
+        .resource_mut::<Events<ClientViolation>>()
+        .drain()
+        .map(|violation| violation.kind)
+        .collect();
+    assert_eq!(violations, [ViolationKind::StepTooFarAhead(tick)]);
+}
+
+#[test]
+fn local_stepping() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+    ))
+    .add_lockstep_commands::<DummyCommand>(1);
+
+    app.world.send_event(DummyCommand(1));
+
+    app.update();
+    app.update();
+
+    let commands: Vec<_> = app
+        .world
+        .resource_mut::<Events<LockstepStep<DummyCommand>>>()
+        .drain()
+        .flat_map(|step| step.commands)
+        .map(|command| (command.client_id, command.event.0))
+        .collect();
+    assert_eq!(commands, [(SERVER_ID, 1)]);
+}
+
+/// Returns ticks of emitted steps with client IDs and values of their commands.
+fn drain_steps(app: &mut App) -> Vec<(NetworkTick, Vec<(u64, u32)>)> {
+    app.world
+        .resource_mut::<Events<LockstepStep<DummyCommand>>>()
+        .drain()
+        .map(|step| {
+            let commands = step
+                .commands
+                .into_iter()
+                .map(|command| (command.client_id, command.event.0))
+                .collect();
+            (step.tick, commands)
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, Deserialize, Event, Serialize)]
+struct DummyCommand(u32);
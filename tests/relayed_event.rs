@@ -0,0 +1,210 @@
+mod common;
+
+use bevy::{ecs::event::Events, prelude::*};
+use bevy_renet::renet::transport::NetcodeClientTransport;
+use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use common::DummyEvent;
+
+#[test]
+fn relaying() {
+    let mut server_app = App::new();
+    let mut client_app1 = App::new();
+    let mut client_app2 = App::new();
+    for app in [&mut server_app, &mut client_app1, &mut client_app2] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_relayed_event::<DummyEvent>(SendPolicy::Ordered);
+    }
+
+    common::connect_multiple(&mut server_app, &mut [&mut client_app1, &mut client_app2]);
+
+    let client_id1 = client_app1
+        .world
+        .resource::<NetcodeClientTransport>()
+        .client_id();
+    let client_id2 = client_app2
+        .world
+        .resource::<NetcodeClientTransport>()
+        .client_id();
+
+    for (mode, server_events_count, events_count1, events_count2) in [
+        (SendMode::Broadcast, 1, 0, 1),
+        (SendMode::Direct(SERVER_ID), 1, 0, 0),
+        (SendMode::Direct(client_id2), 0, 0, 1),
+        (SendMode::BroadcastExcept(client_id2), 1, 0, 0),
+        (SendMode::Clients(vec![client_id1, client_id2]), 0, 0, 1),
+    ] {
+        client_app1
+            .world
+            .resource_mut::<Events<ToPeers<DummyEvent>>>()
+            .send(ToPeers {
+                mode: mode.clone(),
+                event: DummyEvent(Entity::PLACEHOLDER),
+            });
+
+        client_app1.update();
+        server_app.update();
+        client_app1.update();
+        client_app2.update();
+
+        let mut server_events = server_app
+            .world
+            .resource_mut::<Events<FromPeer<DummyEvent>>>();
+        assert_eq!(
+            server_events.drain().count(),
+            server_events_count,
+            "server should receive {server_events_count} events for {mode:?}"
+        );
+
+        for (client_app, events_count) in [
+            (&mut client_app1, events_count1),
+            (&mut client_app2, events_count2),
+        ] {
+            let mut peer_events = client_app
+                .world
+                .resource_mut::<Events<FromPeer<DummyEvent>>>();
+            let events: Vec<_> = peer_events.drain().collect();
+            assert_eq!(
+                events.len(),
+                events_count,
+                "client should receive {events_count} events for {mode:?}"
+            );
+            assert!(events.iter().all(|event| event.sender == client_id1));
+        }
+    }
+}
+
+#[test]
+fn mapping_and_relaying() {
+    let mut server_app = App::new();
+    let mut client_app1 = App::new();
+    let mut client_app2 = App::new();
+    for app in [&mut server_app, &mut client_app1, &mut client_app2] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .replicate::<DummyComponent>()
+        .add_mapped_relayed_event::<DummyEvent>(SendPolicy::Ordered);
+    }
+
+    common::connect_multiple(&mut server_app, &mut [&mut client_app1, &mut client_app2]);
+
+    server_app.world.spawn((Replication, DummyComponent));
+
+    server_app.update();
+    client_app1.update();
+    client_app2.update();
+
+    let client_entity1 = client_app1
+        .world
+        .query_filtered::<Entity, With<DummyComponent>>()
+        .single(&client_app1.world);
+    let client_entity2 = client_app2
+        .world
+        .query_filtered::<Entity, With<DummyComponent>>()
+        .single(&client_app2.world);
+
+    client_app1
+        .world
+        .resource_mut::<Events<ToPeers<DummyEvent>>>()
+        .send(ToPeers {
+            mode: SendMode::Broadcast,
+            event: DummyEvent(client_entity1),
+        });
+
+    client_app1.update();
+    server_app.update();
+    client_app2.update();
+
+    let mapped_entities: Vec<_> = client_app2
+        .world
+        .resource_mut::<Events<FromPeer<DummyEvent>>>()
+        .drain()
+        .map(|event| event.event.0)
+        .collect();
+    assert_eq!(mapped_entities, [client_entity2]);
+}
+
+#[test]
+fn filtering() {
+    let mut server_app = App::new();
+    let mut client_app1 = App::new();
+    let mut client_app2 = App::new();
+    for app in [&mut server_app, &mut client_app1, &mut client_app2] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_relayed_event::<DummyEvent>(SendPolicy::Ordered);
+    }
+
+    server_app.set_relay_filter::<DummyEvent>(|_, mode, _| !matches!(mode, SendMode::Broadcast));
+
+    common::connect_multiple(&mut server_app, &mut [&mut client_app1, &mut client_app2]);
+
+    client_app1
+        .world
+        .resource_mut::<Events<ToPeers<DummyEvent>>>()
+        .send(ToPeers {
+            mode: SendMode::Broadcast,
+            event: DummyEvent(Entity::PLACEHOLDER),
+        });
+
+    client_app1.update();
+    server_app.update();
+    client_app2.update();
+
+    let server_events = server_app.world.resource::<Events<FromPeer<DummyEvent>>>();
+    assert!(server_events.is_empty());
+
+    let peer_events = client_app2.world.resource::<Events<FromPeer<DummyEvent>>>();
+    assert!(peer_events.is_empty());
+}
+
+#[test]
+fn local_relaying() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_relayed_event::<DummyEvent>(SendPolicy::Ordered);
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app
+        .world
+        .resource_mut::<Events<ToPeers<DummyEvent>>>()
+        .send(ToPeers {
+            mode: SendMode::Broadcast,
+            event: DummyEvent(Entity::PLACEHOLDER),
+        });
+
+    server_app.update();
+    client_app.update();
+
+    let server_events = server_app.world.resource::<Events<FromPeer<DummyEvent>>>();
+    assert!(
+        server_events.is_empty(),
+        "sender shouldn't receive its own events"
+    );
+
+    let peer_events: Vec<_> = client_app
+        .world
+        .resource_mut::<Events<FromPeer<DummyEvent>>>()
+        .drain()
+        .map(|event| event.sender)
+        .collect();
+    assert_eq!(peer_events, [SERVER_ID]);
+}
+
+#[derive(Component, Deserialize, Serialize)]
+struct DummyComponent;
@@ -56,6 +56,94 @@ fn tick_acks_receiving() {
     assert_eq!(acked_tick, NetworkTick::new(0));
 }
 
+#[test]
+fn fixed_update_ticks() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::FixedUpdate)),
+        ))
+        .replicate::<TableComponent>()
+        // Prevent fixed steps from running on regular updates.
+        .insert_resource(FixedTime::new_from_secs(1000.0));
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app.world.spawn((TableComponent, Replication));
+
+    let network_tick = *server_app.world.resource::<NetworkTick>();
+    server_app.world.run_schedule(FixedUpdate);
+    server_app.world.run_schedule(FixedUpdate);
+    server_app.update();
+    client_app.update();
+
+    let network_tick = NetworkTick::new(network_tick.get() + 2);
+    assert_eq!(*server_app.world.resource::<NetworkTick>(), network_tick);
+    assert_eq!(**client_app.world.resource::<LastTick>(), network_tick);
+}
+
+#[test]
+fn adaptive_send_rate() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .replicate::<TableComponent>();
+    }
+
+    // Consider the client always congested.
+    const MAX_INTERVAL: u32 = 4;
+    server_app.insert_resource(AdaptiveSendRate {
+        max_interval: MAX_INTERVAL,
+        max_bytes_per_sec: 0.0,
+        ..Default::default()
+    });
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let client_id = client_app
+        .world
+        .resource::<NetcodeClientTransport>()
+        .client_id();
+    let server_entity = server_app.world.spawn((TableComponent, Replication)).id();
+
+    let mut received_ticks = Vec::new();
+    for _ in 0..16 {
+        server_app
+            .world
+            .get_mut::<TableComponent>(server_entity)
+            .unwrap()
+            .set_changed();
+
+        server_app.update();
+        client_app.update();
+
+        let last_tick = **client_app.world.resource::<LastTick>();
+        if received_ticks.last() != Some(&last_tick) {
+            received_ticks.push(last_tick);
+        }
+    }
+
+    let send_rates = server_app.world.resource::<ClientSendRates>();
+    assert_eq!(send_rates.interval(client_id), MAX_INTERVAL);
+
+    let intervals: Vec<_> = received_ticks
+        .windows(2)
+        .map(|ticks| ticks[1].get() - ticks[0].get())
+        .collect();
+    assert_eq!(
+        intervals.last().copied(),
+        Some(MAX_INTERVAL),
+        "updates should be sent every {MAX_INTERVAL} ticks, got {intervals:?}"
+    );
+}
+
 #[test]
 fn spawn_replication() {
     let mut server_app = App::new();
@@ -92,6 +180,41 @@ fn spawn_replication() {
     );
 }
 
+#[cfg(feature = "lz4")]
+#[test]
+fn compressed_replication() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .insert_resource(NetworkCompression {
+            algorithm: CompressionAlgorithm::Lz4,
+            threshold: 0,
+            ..Default::default()
+        })
+        .replicate::<TableComponent>();
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    for _ in 0..10 {
+        server_app.world.spawn((TableComponent, Replication));
+    }
+
+    server_app.update();
+    client_app.update();
+
+    let replicated_count = client_app
+        .world
+        .query_filtered::<(), (With<TableComponent>, With<Replication>)>()
+        .iter(&client_app.world)
+        .count();
+    assert_eq!(replicated_count, 10);
+}
+
 #[test]
 fn insert_replication() {
     let mut server_app = App::new();
@@ -148,6 +271,46 @@ fn insert_replication() {
     );
 }
 
+#[test]
+fn unchanged_entity_replication() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .replicate::<TableComponent>();
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let changed_entity = server_app.world.spawn((Replication, TableComponent)).id();
+    server_app.world.spawn((Replication, TableComponent));
+
+    server_app.update();
+    client_app.update();
+
+    // Change only the first entity, so the second one will have no data to write.
+    server_app
+        .world
+        .entity_mut(changed_entity)
+        .insert(TableComponent);
+    server_app.world.spawn((Replication, TableComponent));
+
+    server_app.update();
+    client_app.update();
+
+    let mut entities = client_app
+        .world
+        .query_filtered::<Entity, (With<Replication>, With<TableComponent>)>();
+    assert_eq!(
+        entities.iter(&client_app.world).count(),
+        3,
+        "entity without changes shouldn't corrupt data of other entities"
+    );
+}
+
 #[test]
 fn removal_replication() {
     let mut server_app = App::new();
@@ -239,6 +402,107 @@ fn despawn_replication() {
     assert!(entity_map.to_server().is_empty());
 }
 
+#[test]
+fn room_replication() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .replicate::<TableComponent>();
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    const ROOM_ID: RoomId = RoomId(0);
+    let server_entity = server_app
+        .world
+        .spawn((Replication, TableComponent, ROOM_ID))
+        .id();
+
+    server_app.update();
+    client_app.update();
+
+    let entity_map = client_app.world.resource::<NetworkEntityMap>();
+    assert!(
+        entity_map.to_client().is_empty(),
+        "entity shouldn't be replicated outside of its room"
+    );
+
+    let client_id = client_app
+        .world
+        .resource::<NetcodeClientTransport>()
+        .client_id();
+    server_app
+        .world
+        .resource_mut::<Rooms>()
+        .add_client(ROOM_ID, client_id);
+
+    server_app.update();
+    client_app.update();
+
+    let entity_map = client_app.world.resource::<NetworkEntityMap>();
+    let client_entity = *entity_map
+        .to_client()
+        .get(&server_entity)
+        .expect("entity should be replicated after joining the room");
+    assert!(client_app
+        .world
+        .get::<TableComponent>(client_entity)
+        .is_some());
+
+    server_app
+        .world
+        .resource_mut::<Rooms>()
+        .remove_client(ROOM_ID, client_id);
+
+    server_app.update();
+    client_app.update();
+
+    assert!(
+        client_app.world.get_entity(client_entity).is_none(),
+        "entity should be despawned after leaving the room"
+    );
+}
+
+#[test]
+fn room_despawn() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ));
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let server_entity = server_app.world.spawn((Replication, RoomId(0))).id();
+
+    server_app.update();
+    client_app.update();
+
+    server_app.world.despawn(server_entity);
+
+    // Pretend that the client has an entity with the same server ID to detect the despawn.
+    let client_entity = client_app.world.spawn_empty().id();
+    client_app
+        .world
+        .resource_mut::<NetworkEntityMap>()
+        .insert(server_entity, client_entity);
+
+    server_app.update();
+    client_app.update();
+
+    assert!(
+        client_app.world.get_entity(client_entity).is_some(),
+        "despawn should be sent only to clients that could see the entity"
+    );
+}
+
 #[test]
 fn replication_into_scene() {
     let mut app = App::new();
@@ -273,6 +537,73 @@ fn replication_into_scene() {
     assert!(empty.components.is_empty());
 }
 
+#[test]
+fn loading_replicated_scene() {
+    let mut scene_app = App::new();
+    scene_app
+        .add_plugins((MinimalPlugins, ReplicationPlugins))
+        .register_type::<ReflectedComponent>()
+        .replicate::<ReflectedComponent>();
+
+    let scene_parent = scene_app
+        .world
+        .spawn((Replication, ReflectedComponent))
+        .id();
+    let scene_child = scene_app
+        .world
+        .spawn((Replication, ReflectedComponent, ParentSync::default()))
+        .id();
+
+    scene_app.update();
+
+    scene_app
+        .world
+        .entity_mut(scene_child)
+        .set_parent(scene_parent);
+
+    scene_app.update();
+
+    let mut scene = DynamicScene::default();
+    server::replicate_into_scene(&mut scene, &scene_app.world);
+
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .register_type::<ReflectedComponent>()
+        .replicate::<ReflectedComponent>();
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let entity_map = server::load_replicated_scene(&scene, &mut server_app.world).unwrap();
+    let server_parent = entity_map.get(scene_parent).unwrap();
+    assert_eq!(
+        server_app
+            .world
+            .query_filtered::<Entity, (With<Replication>, With<ReflectedComponent>)>()
+            .iter(&server_app.world)
+            .count(),
+        2
+    );
+
+    server_app.update();
+    client_app.update();
+
+    let client_parent = client_app.world.resource::<NetworkEntityMap>().to_client()[&server_parent];
+    let client_child_parent = client_app
+        .world
+        .query_filtered::<&Parent, With<ReflectedComponent>>()
+        .single(&client_app.world);
+    assert_eq!(
+        **client_child_parent, client_parent,
+        "hierarchy should be remapped to loaded entities"
+    );
+}
+
 #[derive(Component, Deserialize, Serialize)]
 struct MappedComponent(Entity);
 
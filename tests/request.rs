@@ -0,0 +1,110 @@
+mod common;
+
+use std::time::Duration;
+
+use bevy::{
+    ecs::{event::Events, system::SystemState},
+    prelude::*,
+    time::TimePlugin,
+};
+use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn sending_receiving() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_client_request::<DummyRequest, DummyResponse>(SendPolicy::Ordered, TIMEOUT);
+    }
+    server_app.add_systems(Update, responding_system);
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let id = send_request(&mut client_app);
+
+    client_app.update();
+    server_app.update();
+    client_app.update();
+
+    let results: Vec<_> = client_app
+        .world
+        .resource_mut::<Events<RequestResult<DummyResponse>>>()
+        .drain()
+        .map(|result| (result.id, result.result))
+        .collect();
+    assert_eq!(results, [(id, Ok(DummyResponse))]);
+}
+
+#[test]
+fn local_sending_receiving() {
+    let mut app = App::new();
+    app.add_plugins((
+        TimePlugin,
+        ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+    ))
+    .add_client_request::<DummyRequest, DummyResponse>(SendPolicy::Ordered, TIMEOUT)
+    .add_systems(Update, responding_system);
+
+    let id = send_request(&mut app);
+
+    app.update();
+    app.update();
+    app.update();
+
+    let results: Vec<_> = app
+        .world
+        .resource_mut::<Events<RequestResult<DummyResponse>>>()
+        .drain()
+        .map(|result| (result.id, result.result))
+        .collect();
+    assert_eq!(results, [(id, Ok(DummyResponse))]);
+}
+
+#[test]
+fn timeout() {
+    let mut app = App::new();
+    app.add_plugins((TimePlugin, ReplicationPlugins))
+        .add_client_request::<DummyRequest, DummyResponse>(SendPolicy::Ordered, Duration::ZERO);
+
+    app.update();
+
+    let id = send_request(&mut app);
+
+    app.update();
+
+    let results: Vec<_> = app
+        .world
+        .resource_mut::<Events<RequestResult<DummyResponse>>>()
+        .drain()
+        .map(|result| (result.id, result.result))
+        .collect();
+    assert_eq!(results, [(id, Err(RequestError::Timeout))]);
+}
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+fn send_request(app: &mut App) -> RequestId {
+    let mut system_state: SystemState<RequestSender<DummyRequest>> =
+        SystemState::new(&mut app.world);
+    system_state.get_mut(&mut app.world).send(DummyRequest)
+}
+
+fn responding_system(
+    mut requests: EventReader<FromClient<Request<DummyRequest>>>,
+    mut responses: EventWriter<ToClients<Response<DummyResponse>>>,
+) {
+    for request in &mut requests {
+        responses.send(request.respond(DummyResponse));
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct DummyRequest;
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct DummyResponse;
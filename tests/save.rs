@@ -0,0 +1,278 @@
+use std::{any, env, fs, path::PathBuf, process, thread, time::Duration};
+
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use bincode::{DefaultOptions, Options};
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn write_load() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate::<DummyComponent>()
+        .replicate::<OtherComponent>();
+
+    let saved_entity = app.world.spawn((Replication, DummyComponent(1))).id();
+    app.world.spawn(DummyComponent(2));
+    app.world.spawn((
+        Replication,
+        OtherComponent,
+        Ignored::<OtherComponent>::default(),
+    ));
+
+    let mut bytes = Vec::new();
+    WorldSave::capture(&app.world)
+        .unwrap()
+        .write(&mut bytes)
+        .unwrap();
+
+    let mut load_app = App::new();
+    load_app
+        .add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate::<OtherComponent>()
+        .replicate::<DummyComponent>();
+
+    let world_save = WorldSave::read(&*bytes).unwrap();
+    assert_eq!(world_save.entities().count(), 2);
+
+    let entity_map = world_save.load(&mut load_app.world).unwrap();
+    let loaded_entity = load_app.world.entity(entity_map.get(saved_entity).unwrap());
+    assert!(loaded_entity.contains::<Replication>());
+    assert_eq!(loaded_entity.get::<DummyComponent>().unwrap().0, 1);
+
+    let mut components = load_app.world.query::<&DummyComponent>();
+    assert_eq!(
+        components.iter(&load_app.world).count(),
+        1,
+        "only replicated entities should be saved"
+    );
+    let mut components = load_app.world.query::<&OtherComponent>();
+    assert_eq!(
+        components.iter(&load_app.world).count(),
+        0,
+        "ignored components shouldn't be saved"
+    );
+}
+
+#[test]
+fn mapped_component() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate_mapped::<MappedComponent>();
+
+    let saved_entity = app.world.spawn(Replication).id();
+    let saved_mapped_entity = app
+        .world
+        .spawn((Replication, MappedComponent(saved_entity)))
+        .id();
+
+    let world_save = WorldSave::capture(&app.world).unwrap();
+
+    app.world.spawn_empty();
+    let entity_map = world_save.load(&mut app.world).unwrap();
+
+    let loaded_entity = entity_map.get(saved_entity).unwrap();
+    let loaded_mapped_entity = entity_map.get(saved_mapped_entity).unwrap();
+    assert_ne!(loaded_entity, saved_entity);
+
+    let mapped_component = app
+        .world
+        .get::<MappedComponent>(loaded_mapped_entity)
+        .unwrap();
+    assert_eq!(mapped_component.0, loaded_entity);
+}
+
+#[test]
+fn unknown_component() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate::<DummyComponent>();
+
+    app.world.spawn((Replication, DummyComponent(0)));
+
+    let world_save = WorldSave::capture(&app.world).unwrap();
+
+    let mut load_app = App::new();
+    load_app.add_plugins((MinimalPlugins, ReplicationPlugins));
+
+    let result = world_save.load(&mut load_app.world);
+    assert!(matches!(result, Err(SaveError::UnknownComponent(_))));
+}
+
+#[test]
+fn renaming() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate::<DummyComponent>();
+
+    app.world.spawn((Replication, DummyComponent(1)));
+
+    let world_save = WorldSave::capture(&app.world).unwrap();
+
+    let mut load_app = App::new();
+    load_app
+        .add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate::<RenamedComponent>()
+        .rename_saved_component::<RenamedComponent>(any::type_name::<DummyComponent>());
+
+    world_save.load(&mut load_app.world).unwrap();
+
+    let component = load_app
+        .world
+        .query::<&RenamedComponent>()
+        .single(&load_app.world);
+    assert_eq!(component.0, 1);
+}
+
+#[test]
+fn layout_migration() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate::<DummyComponent>();
+
+    app.world.spawn((Replication, DummyComponent(1)));
+
+    let world_save = WorldSave::capture(&app.world).unwrap();
+    assert_eq!(world_save.version(), 0);
+
+    let mut load_app = App::new();
+    load_app
+        .add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate::<RenamedComponent>()
+        .set_save_version(2)
+        .rename_saved_component::<RenamedComponent>(any::type_name::<DummyComponent>())
+        .migrate_saved_component::<RenamedComponent>(2, |bytes| {
+            let value: u32 = DefaultOptions::new().deserialize(bytes)?;
+            DefaultOptions::new().serialize(&(value * 10))
+        })
+        .migrate_saved_component::<RenamedComponent>(1, |bytes| {
+            let value: u32 = DefaultOptions::new().deserialize(bytes)?;
+            DefaultOptions::new().serialize(&(value + 1))
+        });
+
+    world_save.load(&mut load_app.world).unwrap();
+
+    let component = load_app
+        .world
+        .query::<&RenamedComponent>()
+        .single(&load_app.world);
+    assert_eq!(component.0, 20, "migrations should be applied in order");
+
+    let world_save = WorldSave::capture(&load_app.world).unwrap();
+    assert_eq!(world_save.version(), 2);
+
+    world_save.load(&mut load_app.world).unwrap();
+
+    let mut components = load_app.world.query::<&RenamedComponent>();
+    assert!(
+        components
+            .iter(&load_app.world)
+            .all(|component| component.0 == 20),
+        "migrations shouldn't be applied to saves with the current version"
+    );
+}
+
+#[test]
+fn newer_version() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate::<DummyComponent>()
+        .set_save_version(1);
+
+    app.world.spawn((Replication, DummyComponent(1)));
+
+    let world_save = WorldSave::capture(&app.world).unwrap();
+
+    let mut load_app = App::new();
+    load_app
+        .add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate::<DummyComponent>();
+
+    let result = world_save.load(&mut load_app.world);
+    assert!(matches!(result, Err(SaveError::UnsupportedVersion(1))));
+}
+
+#[test]
+fn autosave() {
+    let dir = env::temp_dir().join(format!("bevy_replicon_autosave_{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("world.sav");
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        ReplicationPlugins,
+        AutosavePlugin::new(&path, Duration::ZERO),
+    ))
+    .replicate::<DummyComponent>();
+
+    assert!(app.world.resource::<Autosave>().latest().unwrap().is_none());
+
+    let entity = app.world.spawn((Replication, DummyComponent(1))).id();
+
+    wait_autosave(&mut app);
+
+    app.world.get_mut::<DummyComponent>(entity).unwrap().0 = 2;
+
+    wait_autosave(&mut app);
+
+    for slot in 0..2 {
+        let mut slot_path = path.clone().into_os_string();
+        slot_path.push(format!(".{slot}"));
+        assert!(
+            PathBuf::from(slot_path).exists(),
+            "saves should alternate between files"
+        );
+    }
+
+    let mut load_app = App::new();
+    load_app
+        .add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins,
+            AutosavePlugin::new(&path, Duration::MAX),
+        ))
+        .replicate::<DummyComponent>();
+
+    let world_save = load_app
+        .world
+        .resource::<Autosave>()
+        .latest()
+        .unwrap()
+        .expect("autosave should be written");
+    world_save.load(&mut load_app.world).unwrap();
+
+    let component = load_app
+        .world
+        .query::<&DummyComponent>()
+        .single(&load_app.world);
+    assert_eq!(component.0, 2, "the latest save should be loaded");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+/// Runs update to trigger autosave and waits until it's written.
+fn wait_autosave(app: &mut App) {
+    app.update();
+    while app.world.resource::<Autosave>().is_saving() {
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[derive(Component, Deserialize, Serialize)]
+struct DummyComponent(u32);
+
+#[derive(Component, Deserialize, Serialize)]
+struct OtherComponent;
+
+#[derive(Component, Deserialize, Serialize)]
+struct RenamedComponent(u32);
+
+#[derive(Component, Deserialize, Serialize)]
+struct MappedComponent(Entity);
+
+impl MapNetworkEntities for MappedComponent {
+    fn map_entities<T: Mapper>(&mut self, mapper: &mut T) {
+        self.0 = mapper.map(self.0);
+    }
+}
@@ -4,6 +4,7 @@ use bevy::prelude::*;
 use bevy::{ecs::event::Events, time::TimePlugin};
 use bevy_renet::renet::transport::NetcodeClientTransport;
 use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use common::{DummyEvent, ReflectEvent, ReflectEventDeserializer, ReflectEventSerializer};
 
@@ -45,18 +46,27 @@ fn sending_receiving() {
         .world
         .resource::<NetcodeClientTransport>()
         .client_id();
+    const ROOM_ID: RoomId = RoomId(0);
+    server_app
+        .world
+        .resource_mut::<Rooms>()
+        .add_client(ROOM_ID, client_id);
     for (mode, events_count) in [
         (SendMode::Broadcast, 1),
         (SendMode::Direct(SERVER_ID), 0),
         (SendMode::Direct(client_id), 1),
         (SendMode::BroadcastExcept(SERVER_ID), 1),
         (SendMode::BroadcastExcept(client_id), 0),
+        (SendMode::Room(ROOM_ID), 1),
+        (SendMode::Room(RoomId(1)), 0),
+        (SendMode::Clients(vec![client_id]), 1),
+        (SendMode::Clients(vec![SERVER_ID]), 0),
     ] {
         server_app
             .world
             .resource_mut::<Events<ToClients<DummyEvent>>>()
             .send(ToClients {
-                mode,
+                mode: mode.clone(),
                 event: DummyEvent(Entity::PLACEHOLDER),
             });
 
@@ -72,6 +82,161 @@ fn sending_receiving() {
     }
 }
 
+#[test]
+fn compressed_sending_receiving() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .insert_resource(NetworkCompression {
+            #[cfg(feature = "lz4")]
+            algorithm: CompressionAlgorithm::Lz4,
+            threshold: 0,
+            ..Default::default()
+        })
+        .add_server_event::<DummyEvent>(SendPolicy::Ordered)
+        .compress_event::<DummyEvent>();
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app
+        .world
+        .resource_mut::<Events<ToClients<DummyEvent>>>()
+        .send(ToClients {
+            mode: SendMode::Broadcast,
+            event: DummyEvent(Entity::PLACEHOLDER),
+        });
+
+    server_app.update();
+    client_app.update();
+
+    let dummy_events = client_app.world.resource::<Events<DummyEvent>>();
+    assert_eq!(dummy_events.len(), 1);
+}
+
+#[test]
+fn sending_with_next_tick() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::Manual)),
+        ))
+        .add_server_event::<DummyEvent>(SendPolicy::Ordered);
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app
+        .world
+        .resource_mut::<Events<ToClients<DummyEvent>>>()
+        .send(ToClients {
+            mode: SendMode::Broadcast,
+            event: DummyEvent(Entity::PLACEHOLDER),
+        });
+
+    // Run more frames than events live to ensure that they are not dropped.
+    for _ in 0..3 {
+        server_app.update();
+        client_app.update();
+    }
+
+    let dummy_events = client_app.world.resource::<Events<DummyEvent>>();
+    assert!(dummy_events.is_empty());
+
+    server_app.add_systems(
+        PostUpdate,
+        ServerPlugin::increment_tick.before(ServerSet::Send),
+    );
+
+    server_app.update();
+    client_app.update();
+
+    let dummy_events = client_app.world.resource::<Events<DummyEvent>>();
+    assert_eq!(dummy_events.len(), 1);
+}
+
+#[test]
+fn sending_immediately() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::Manual)),
+        ))
+        .add_server_event::<DummyEvent>(SendPolicy::Ordered)
+        .batch_server_event::<DummyEvent>();
+    }
+
+    server_app.set_send_timing::<DummyEvent>(SendTiming::Immediate);
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app
+        .world
+        .resource_mut::<Events<ToClients<DummyEvent>>>()
+        .extend(
+            [SendMode::Broadcast, SendMode::Broadcast].map(|mode| ToClients {
+                mode,
+                event: DummyEvent(Entity::PLACEHOLDER),
+            }),
+        );
+
+    server_app.update();
+    client_app.update();
+
+    let dummy_events = client_app.world.resource::<Events<DummyEvent>>();
+    assert_eq!(dummy_events.len(), 2);
+}
+
+#[test]
+fn batched_sending_receiving() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_server_event::<DummyEvent>(SendPolicy::Ordered)
+        .batch_server_event::<DummyEvent>();
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let client_id = client_app
+        .world
+        .resource::<NetcodeClientTransport>()
+        .client_id();
+    let mut server_events = server_app
+        .world
+        .resource_mut::<Events<ToClients<DummyEvent>>>();
+    for mode in [
+        SendMode::Broadcast,
+        SendMode::Direct(client_id),
+        SendMode::Direct(SERVER_ID),
+        SendMode::BroadcastExcept(client_id),
+        SendMode::Clients(vec![client_id]),
+    ] {
+        server_events.send(ToClients {
+            mode,
+            event: DummyEvent(Entity::PLACEHOLDER),
+        });
+    }
+
+    server_app.update();
+    client_app.update();
+
+    let dummy_events = client_app.world.resource::<Events<DummyEvent>>();
+    assert_eq!(dummy_events.len(), 3);
+}
+
 #[test]
 fn sending_receiving_and_mapping() {
     let mut server_app = App::new();
@@ -113,6 +278,241 @@ fn sending_receiving_and_mapping() {
     assert_eq!(mapped_entities, [client_entity]);
 }
 
+#[test]
+fn unmapped_dropping() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_mapped_server_event::<DummyEvent>(SendPolicy::Ordered);
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app
+        .world
+        .resource_mut::<Events<ToClients<DummyEvent>>>()
+        .send(ToClients {
+            mode: SendMode::Broadcast,
+            event: DummyEvent(Entity::from_raw(0)),
+        });
+
+    server_app.update();
+    client_app.update();
+
+    let dummy_events = client_app.world.resource::<Events<DummyEvent>>();
+    assert!(dummy_events.is_empty());
+
+    let diagnostics = client_app
+        .world
+        .resource::<EventMappingDiagnostics<DummyEvent>>();
+    assert_eq!(diagnostics.dropped, 1);
+}
+
+#[test]
+fn unmapped_placeholder() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_mapped_server_event::<DummyEvent>(SendPolicy::Ordered)
+        .set_mapping_policy::<DummyEvent>(MappingPolicy::Placeholder);
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app
+        .world
+        .resource_mut::<Events<ToClients<DummyEvent>>>()
+        .send(ToClients {
+            mode: SendMode::Broadcast,
+            event: DummyEvent(Entity::from_raw(0)),
+        });
+
+    server_app.update();
+    client_app.update();
+
+    let mapped_entities: Vec<_> = client_app
+        .world
+        .resource_mut::<Events<DummyEvent>>()
+        .drain()
+        .map(|event| event.0)
+        .collect();
+    assert_eq!(mapped_entities, [Entity::PLACEHOLDER]);
+
+    let diagnostics = client_app
+        .world
+        .resource::<EventMappingDiagnostics<DummyEvent>>();
+    assert_eq!(diagnostics.placeholders, 1);
+}
+
+#[test]
+fn unmapped_holding() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_mapped_server_event::<DummyEvent>(SendPolicy::Ordered)
+        .set_mapping_policy::<DummyEvent>(MappingPolicy::Hold(u32::MAX));
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let client_entity = Entity::from_raw(0);
+    let server_entity = Entity::from_raw(client_entity.index() + 1);
+    server_app
+        .world
+        .resource_mut::<Events<ToClients<DummyEvent>>>()
+        .send(ToClients {
+            mode: SendMode::Broadcast,
+            event: DummyEvent(server_entity),
+        });
+
+    server_app.update();
+    client_app.update();
+
+    let dummy_events = client_app.world.resource::<Events<DummyEvent>>();
+    assert!(dummy_events.is_empty());
+
+    client_app
+        .world
+        .resource_mut::<NetworkEntityMap>()
+        .insert(server_entity, client_entity);
+
+    client_app.update();
+
+    let mapped_entities: Vec<_> = client_app
+        .world
+        .resource_mut::<Events<DummyEvent>>()
+        .drain()
+        .map(|event| event.0)
+        .collect();
+    assert_eq!(mapped_entities, [client_entity]);
+
+    let diagnostics = client_app
+        .world
+        .resource::<EventMappingDiagnostics<DummyEvent>>();
+    assert_eq!(diagnostics.held, 1);
+    assert_eq!(diagnostics.dropped, 0);
+}
+
+#[test]
+fn unmapped_holding_order() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_mapped_server_event::<DummyEvent>(SendPolicy::Ordered)
+        .set_mapping_policy::<DummyEvent>(MappingPolicy::Hold(u32::MAX));
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let client_entity = Entity::from_raw(0);
+    let server_entity = Entity::from_raw(client_entity.index() + 1);
+    let mapped_client_entity = Entity::from_raw(2);
+    let mapped_server_entity = Entity::from_raw(mapped_client_entity.index() + 1);
+    client_app
+        .world
+        .resource_mut::<NetworkEntityMap>()
+        .insert(mapped_server_entity, mapped_client_entity);
+
+    let mut to_clients = server_app
+        .world
+        .resource_mut::<Events<ToClients<DummyEvent>>>();
+    for entity in [server_entity, mapped_server_entity] {
+        to_clients.send(ToClients {
+            mode: SendMode::Broadcast,
+            event: DummyEvent(entity),
+        });
+    }
+
+    server_app.update();
+    client_app.update();
+
+    let dummy_events = client_app.world.resource::<Events<DummyEvent>>();
+    assert!(
+        dummy_events.is_empty(),
+        "event after a held event should be held too"
+    );
+
+    client_app
+        .world
+        .resource_mut::<NetworkEntityMap>()
+        .insert(server_entity, client_entity);
+
+    client_app.update();
+
+    let mapped_entities: Vec<_> = client_app
+        .world
+        .resource_mut::<Events<DummyEvent>>()
+        .drain()
+        .map(|event| event.0)
+        .collect();
+    assert_eq!(mapped_entities, [client_entity, mapped_client_entity]);
+}
+
+#[test]
+fn unmapped_holding_expiration() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .replicate::<DummyComponent>()
+        .add_mapped_server_event::<DummyEvent>(SendPolicy::Ordered)
+        .set_mapping_policy::<DummyEvent>(MappingPolicy::Hold(1));
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app
+        .world
+        .resource_mut::<Events<ToClients<DummyEvent>>>()
+        .send(ToClients {
+            mode: SendMode::Broadcast,
+            event: DummyEvent(Entity::PLACEHOLDER),
+        });
+
+    server_app.update();
+    client_app.update();
+
+    let diagnostics = client_app
+        .world
+        .resource::<EventMappingDiagnostics<DummyEvent>>();
+    assert_eq!(diagnostics.held, 1);
+    assert_eq!(diagnostics.dropped, 0);
+
+    // Spawn entities to receive more server ticks.
+    for _ in 0..2 {
+        server_app.world.spawn((DummyComponent, Replication));
+        server_app.update();
+        client_app.update();
+    }
+
+    let dummy_events = client_app.world.resource::<Events<DummyEvent>>();
+    assert!(dummy_events.is_empty());
+
+    let diagnostics = client_app
+        .world
+        .resource::<EventMappingDiagnostics<DummyEvent>>();
+    assert_eq!(diagnostics.dropped, 1);
+}
+
 #[test]
 fn sending_receiving_reflect() {
     let mut server_app = App::new();
@@ -134,18 +534,27 @@ fn sending_receiving_reflect() {
         .world
         .resource::<NetcodeClientTransport>()
         .client_id();
+    const ROOM_ID: RoomId = RoomId(0);
+    server_app
+        .world
+        .resource_mut::<Rooms>()
+        .add_client(ROOM_ID, client_id);
     for (mode, events_count) in [
         (SendMode::Broadcast, 1),
         (SendMode::Direct(SERVER_ID), 0),
         (SendMode::Direct(client_id), 1),
         (SendMode::BroadcastExcept(SERVER_ID), 1),
         (SendMode::BroadcastExcept(client_id), 0),
+        (SendMode::Room(ROOM_ID), 1),
+        (SendMode::Room(RoomId(1)), 0),
+        (SendMode::Clients(vec![client_id]), 1),
+        (SendMode::Clients(vec![SERVER_ID]), 0),
     ] {
         server_app
             .world
             .resource_mut::<Events<ToClients<ReflectEvent>>>()
             .send(ToClients {
-                mode,
+                mode: mode.clone(),
                 event: ReflectEvent {
                     entity: Entity::PLACEHOLDER,
                     reflect: ReflectedValue.clone_value(),
@@ -164,6 +573,40 @@ fn sending_receiving_reflect() {
     }
 }
 
+#[test]
+fn sending_receiving_reflect_serde() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_server_event_reflect::<ReflectValueEvent>(SendPolicy::Ordered);
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app
+        .world
+        .resource_mut::<Events<ToClients<ReflectValueEvent>>>()
+        .send(ToClients {
+            mode: SendMode::Broadcast,
+            event: ReflectValueEvent(1),
+        });
+
+    server_app.update();
+    client_app.update();
+
+    let values: Vec<_> = client_app
+        .world
+        .resource_mut::<Events<ReflectValueEvent>>()
+        .drain()
+        .map(|event| event.0)
+        .collect();
+    assert_eq!(values, [1]);
+}
+
 #[test]
 fn sending_receiving_and_mapping_reflect() {
     let mut server_app = App::new();
@@ -219,17 +662,25 @@ fn local_resending() {
     .add_server_event::<DummyEvent>(SendPolicy::Ordered);
 
     const DUMMY_CLIENT_ID: u64 = 1;
+    const ROOM_ID: RoomId = RoomId(0);
+    app.world
+        .resource_mut::<Rooms>()
+        .add_client(ROOM_ID, SERVER_ID);
     for (mode, events_count) in [
         (SendMode::Broadcast, 1),
         (SendMode::Direct(SERVER_ID), 1),
         (SendMode::Direct(DUMMY_CLIENT_ID), 0),
         (SendMode::BroadcastExcept(SERVER_ID), 0),
         (SendMode::BroadcastExcept(DUMMY_CLIENT_ID), 1),
+        (SendMode::Room(ROOM_ID), 1),
+        (SendMode::Room(RoomId(1)), 0),
+        (SendMode::Clients(vec![SERVER_ID]), 1),
+        (SendMode::Clients(vec![DUMMY_CLIENT_ID]), 0),
     ] {
         app.world
             .resource_mut::<Events<ToClients<DummyEvent>>>()
             .send(ToClients {
-                mode,
+                mode: mode.clone(),
                 event: DummyEvent(Entity::PLACEHOLDER),
             });
 
@@ -246,3 +697,9 @@ fn local_resending() {
         );
     }
 }
+
+#[derive(Component, Deserialize, Serialize)]
+struct DummyComponent;
+
+#[derive(Debug, Event, Reflect)]
+struct ReflectValueEvent(u32);
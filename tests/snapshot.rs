@@ -0,0 +1,191 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn capture_restore() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate::<DummyComponent>()
+        .replicate::<OtherComponent>();
+
+    let entity = app.world.spawn((Replication, DummyComponent(0))).id();
+
+    let snapshot = ReplicationSnapshot::capture(&app.world).unwrap();
+    assert_eq!(snapshot.entities().collect::<Vec<_>>(), [entity]);
+
+    app.world
+        .entity_mut(entity)
+        .insert((DummyComponent(1), OtherComponent));
+
+    snapshot.restore(&mut app.world).unwrap();
+
+    let entity = app.world.entity(entity);
+    assert_eq!(entity.get::<DummyComponent>().unwrap().0, 0);
+    assert!(
+        !entity.contains::<OtherComponent>(),
+        "component inserted after capture should be removed"
+    );
+}
+
+#[test]
+fn respawning() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate::<DummyComponent>()
+        .replicate_mapped::<MappedComponent>();
+
+    let entity = app.world.spawn((Replication, DummyComponent(0))).id();
+    let mapped_entity = app.world.spawn((Replication, MappedComponent(entity))).id();
+
+    let snapshot = ReplicationSnapshot::capture(&app.world).unwrap();
+
+    app.world.despawn(entity);
+
+    snapshot.restore(&mut app.world).unwrap();
+
+    let new_entity = app
+        .world
+        .query_filtered::<Entity, With<DummyComponent>>()
+        .single(&app.world);
+    assert_ne!(new_entity, entity);
+
+    let mapped_component = app.world.get::<MappedComponent>(mapped_entity).unwrap();
+    assert_eq!(
+        mapped_component.0, new_entity,
+        "reference to despawned entity should be updated"
+    );
+}
+
+#[test]
+fn unknown_reference() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate_mapped::<MappedComponent>();
+
+    let unknown_entity = app.world.spawn_empty().id();
+    let entity = app
+        .world
+        .spawn((Replication, MappedComponent(unknown_entity)))
+        .id();
+
+    let snapshot = ReplicationSnapshot::capture(&app.world).unwrap();
+
+    app.world.despawn(unknown_entity);
+
+    snapshot.restore(&mut app.world).unwrap();
+
+    let mapped_component = app.world.get::<MappedComponent>(entity).unwrap();
+    assert_eq!(
+        mapped_component.0,
+        Entity::PLACEHOLDER,
+        "unknown reference should be mapped to placeholder"
+    );
+    assert_eq!(
+        app.world.entities().len(),
+        1,
+        "no entities should be spawned"
+    );
+}
+
+#[test]
+fn client_respawning() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .replicate::<DummyComponent>();
+    }
+
+    common::connect(&mut server_app, &mut client_app);
+
+    let server_entity = server_app
+        .world
+        .spawn((Replication, DummyComponent(0)))
+        .id();
+
+    server_app.update();
+    client_app.update();
+
+    let client_entity = client_app.world.resource::<NetworkEntityMap>().to_client()[&server_entity];
+    let snapshot = ReplicationSnapshot::capture(&client_app.world).unwrap();
+
+    client_app.world.despawn(client_entity);
+
+    snapshot.restore(&mut client_app.world).unwrap();
+
+    let entity_map = client_app.world.resource::<NetworkEntityMap>();
+    let new_entity = entity_map.to_client()[&server_entity];
+    assert_ne!(new_entity, client_entity);
+    assert_eq!(entity_map.to_server()[&new_entity], server_entity);
+    assert!(!entity_map.to_server().contains_key(&client_entity));
+
+    server_app
+        .world
+        .get_mut::<DummyComponent>(server_entity)
+        .unwrap()
+        .0 = 1;
+
+    server_app.update();
+    client_app.update();
+
+    let component = client_app.world.get::<DummyComponent>(new_entity).unwrap();
+    assert_eq!(
+        component.0, 1,
+        "respawned entity should receive updates from server"
+    );
+}
+
+#[test]
+fn filtering() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ReplicationPlugins))
+        .replicate::<DummyComponent>();
+
+    let filtered_entity = app
+        .world
+        .spawn((Replication, DummyComponent(0), FilterMarker))
+        .id();
+    let entity = app.world.spawn((Replication, DummyComponent(0))).id();
+
+    let snapshot = ReplicationSnapshot::capture_filtered(&app.world, |entity| {
+        entity.contains::<FilterMarker>()
+    })
+    .unwrap();
+    assert_eq!(snapshot.entities().collect::<Vec<_>>(), [filtered_entity]);
+
+    for entity in [filtered_entity, entity] {
+        app.world.get_mut::<DummyComponent>(entity).unwrap().0 = 1;
+    }
+
+    snapshot.restore(&mut app.world).unwrap();
+
+    assert_eq!(
+        app.world.get::<DummyComponent>(filtered_entity).unwrap().0,
+        0
+    );
+    assert_eq!(app.world.get::<DummyComponent>(entity).unwrap().0, 1);
+}
+
+#[derive(Component, Deserialize, Serialize)]
+struct DummyComponent(u32);
+
+#[derive(Component, Deserialize, Serialize)]
+struct OtherComponent;
+
+#[derive(Component, Deserialize, Serialize)]
+struct MappedComponent(Entity);
+
+impl MapNetworkEntities for MappedComponent {
+    fn map_entities<T: Mapper>(&mut self, mapper: &mut T) {
+        self.0 = mapper.map(self.0);
+    }
+}
+
+#[derive(Component)]
+struct FilterMarker;
@@ -0,0 +1,144 @@
+mod common;
+
+use bevy::{ecs::event::Events, prelude::*};
+use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[test]
+fn last_retention() {
+    let values = replay(Retention::Last(2), [0, 1, 2]);
+    assert_eq!(values, [1, 2]);
+}
+
+#[test]
+fn last_per_key_retention() {
+    let values = replay(
+        Retention::LastPerKey(|event: &DummyEvent| event.key),
+        [0, 1, 2],
+    );
+    assert_eq!(values, [0, 2]);
+}
+
+#[test]
+fn until_cleared_retention() {
+    let values = replay(Retention::UntilCleared, [0, 1, 2]);
+    assert_eq!(values, [0, 1, 2]);
+}
+
+#[test]
+fn batched_replay() {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_sticky_server_event::<DummyEvent>(SendPolicy::Ordered, Retention::UntilCleared)
+        .batch_server_event::<DummyEvent>();
+    }
+
+    server_app
+        .world
+        .resource_mut::<Events<ToClients<DummyEvent>>>()
+        .send(ToClients {
+            mode: SendMode::Broadcast,
+            event: DummyEvent { key: 0, value: 0 },
+        });
+
+    server_app.update();
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app.update();
+    client_app.update();
+
+    let dummy_events = client_app.world.resource::<Events<DummyEvent>>();
+    assert_eq!(dummy_events.len(), 1);
+}
+
+#[test]
+fn clearing() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+    ))
+    .add_sticky_server_event::<DummyEvent>(SendPolicy::Ordered, Retention::UntilCleared);
+
+    app.world
+        .resource_mut::<Events<ToClients<DummyEvent>>>()
+        .send(ToClients {
+            mode: SendMode::Broadcast,
+            event: DummyEvent { key: 0, value: 0 },
+        });
+
+    app.update();
+
+    let mut sticky_events = app.world.resource_mut::<StickyEvents<DummyEvent>>();
+    assert_eq!(sticky_events.len(), 1);
+
+    sticky_events.clear();
+    assert!(sticky_events.is_empty());
+}
+
+/// Broadcasts events with the specified values before client connection
+/// and returns values that client received.
+///
+/// Events with values 1 and 2 share the same key.
+fn replay(retention: Retention<DummyEvent>, values: [u32; 3]) -> Vec<u32> {
+    let mut server_app = App::new();
+    let mut client_app = App::new();
+    for app in [&mut server_app, &mut client_app] {
+        app.add_plugins((
+            MinimalPlugins,
+            ReplicationPlugins.set(ServerPlugin::new(TickPolicy::EveryFrame)),
+        ))
+        .add_sticky_server_event::<DummyEvent>(SendPolicy::Ordered, retention);
+    }
+
+    for value in values {
+        server_app
+            .world
+            .resource_mut::<Events<ToClients<DummyEvent>>>()
+            .send(ToClients {
+                mode: SendMode::Broadcast,
+                event: DummyEvent {
+                    key: value.min(1).into(),
+                    value,
+                },
+            });
+    }
+    server_app
+        .world
+        .resource_mut::<Events<ToClients<DummyEvent>>>()
+        .send(ToClients {
+            mode: SendMode::Direct(SERVER_ID),
+            event: DummyEvent {
+                key: 0,
+                value: u32::MAX,
+            },
+        });
+
+    server_app.update();
+
+    common::connect(&mut server_app, &mut client_app);
+
+    server_app.update();
+    client_app.update();
+
+    let mut reader = client_app
+        .world
+        .resource::<Events<DummyEvent>>()
+        .get_reader();
+    reader
+        .iter(client_app.world.resource::<Events<DummyEvent>>())
+        .map(|event| event.value)
+        .collect()
+}
+
+#[derive(Debug, Deserialize, Event, Serialize)]
+struct DummyEvent {
+    key: u64,
+    value: u32,
+}